@@ -103,7 +103,7 @@ END
 
 # Build
 echo "cargo build"
-cargo build --release --target wasm32-unknown-unknown
+cargo build --release --target wasm32-unknown-unknown --features wasm
 
 # Generate bindgen outputs
 mkdir -p dist
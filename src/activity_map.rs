@@ -0,0 +1,218 @@
+use std::{fmt::Write, ops::RangeInclusive};
+
+/// Tracks which parts of the board changed since the last step
+/// the board is divided into square tiles, a changed cell makes its tile active
+/// the tiles are about as large as the kernel, so a single changed cell costs
+/// about `(tile_size + convolution_width)²` evaluated cells independent of the board size
+#[derive(Clone, Debug)]
+pub struct ActivityMap {
+    // width and height of the field
+    width: usize,
+    height: usize,
+    tile_size: usize,
+    tiles_x: usize,
+    tiles_y: usize,
+    bactive: Vec<bool>,
+    /// indices into `bactive` of the active tiles, clearing only touches these
+    active: Vec<usize>,
+}
+
+impl ActivityMap {
+    /// all tiles are active
+    pub fn new(width: usize, height: usize, convolution_width: usize) -> Self {
+        let tile_size = Self::choose_tile_size(width, height, convolution_width);
+        let tiles_x = width.div_ceil(tile_size);
+        let tiles_y = height.div_ceil(tile_size);
+        let mut res = ActivityMap {
+            width,
+            height,
+            tile_size,
+            tiles_x,
+            tiles_y,
+            bactive: vec![false; tiles_x * tiles_y],
+            active: Vec::new(),
+        };
+        res.everything_changed();
+        res
+    }
+
+    /// every active tile is evaluated with a margin of half the kernel on each side,
+    /// smaller tiles waste less work on unchanged cells but give more ranges
+    /// boards smaller than a tile are a single tile
+    fn choose_tile_size(width: usize, height: usize, convolution_width: usize) -> usize {
+        convolution_width.max(4).min(width.max(height)).max(1)
+    }
+
+    pub fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    pub fn clear(&mut self) {
+        for tile in self.active.drain(..) {
+            self.bactive[tile] = false;
+        }
+    }
+
+    pub fn everything_changed(&mut self) {
+        self.bactive.fill(true);
+        self.active.clear();
+        self.active.extend(0..self.bactive.len());
+    }
+
+    /// marks the cell at (x, y) as changed
+    pub fn insert(&mut self, x: usize, y: usize) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let tile = (y / self.tile_size) * self.tiles_x + x / self.tile_size;
+        if !self.bactive[tile] {
+            self.bactive[tile] = true;
+            self.active.push(tile);
+        }
+    }
+
+    /// false if nothing was inserted since the last `clear`
+    pub fn has_changes(&self) -> bool {
+        !self.active.is_empty()
+    }
+
+    pub fn num_active(&self) -> usize {
+        self.active.len()
+    }
+
+    /// the cells which have to be evaluated again, `(x_start, x_end)..=(y_start, y_end)`
+    /// neighbouring active tiles in a row are merged into one range
+    pub fn changed_ranges(&self, convolution_width: usize) -> Vec<RangeInclusive<(usize, usize)>> {
+        let wh = convolution_width / 2;
+        let mut tiles = self.active.clone();
+        tiles.sort_unstable();
+        let mut res = Vec::new();
+        let mut ix = 0;
+        while ix < tiles.len() {
+            let first = tiles[ix];
+            let mut last = first;
+            while ix + 1 < tiles.len()
+                && tiles[ix + 1] == last + 1
+                && tiles[ix + 1] / self.tiles_x == first / self.tiles_x
+            {
+                ix += 1;
+                last = tiles[ix];
+            }
+            ix += 1;
+            let (tile_y, first_x, last_x) = (
+                first / self.tiles_x,
+                first % self.tiles_x,
+                last % self.tiles_x,
+            );
+            let x_start = (first_x * self.tile_size).saturating_sub(wh);
+            let x_end = ((last_x + 1) * self.tile_size - 1 + wh).min(self.width - 1);
+            let y_start = (tile_y * self.tile_size).saturating_sub(wh);
+            let y_end = ((tile_y + 1) * self.tile_size - 1 + wh).min(self.height - 1);
+            res.push((x_start, x_end)..=(y_start, y_end));
+        }
+        res
+    }
+
+    /// one line per row of tiles, X for active and _ for inactive tiles
+    pub fn debug_tiles(&self) -> Result<String, std::fmt::Error> {
+        let mut res = String::new();
+        for tile_y in 0..self.tiles_y {
+            for tile_x in 0..self.tiles_x {
+                let c = if self.bactive[tile_y * self.tiles_x + tile_x] {
+                    'X'
+                } else {
+                    '_'
+                };
+                res.write_char(c)?;
+            }
+            writeln!(res)?;
+        }
+        Ok(res)
+    }
+}
+
+#[test]
+fn changed_ranges_of_active_tiles() {
+    let mut map = ActivityMap::new(800, 400, 7);
+    assert_eq!(map.tile_size(), 7);
+    assert_eq!(map.changed_ranges(7).len(), 58);
+    map.clear();
+    assert!(!map.has_changes());
+    map.insert(100, 100);
+    map.insert(102, 101);
+    assert_eq!(map.changed_ranges(7), vec![(95, 107)..=(95, 107)]);
+    map.insert(0, 0);
+    map.insert(7, 0);
+    map.insert(799, 399);
+    assert_eq!(
+        map.changed_ranges(7),
+        vec![
+            (0, 16)..=(0, 9),
+            (95, 107)..=(95, 107),
+            (795, 799)..=(396, 399)
+        ]
+    );
+    let small = ActivityMap::new(10, 5, 7);
+    assert_eq!(small.changed_ranges(7), vec![(0, 9)..=(0, 4)]);
+}
+
+#[test]
+fn changed_ranges_cover_the_kernels_at_tile_edges() {
+    // every cell whose kernel reaches a changed cell is in one of the ranges
+    let covers = |map: &ActivityMap, cw: usize, changed: &[(usize, usize)]| {
+        let ranges = map.changed_ranges(cw);
+        let wh = cw / 2;
+        changed.iter().all(|(x, y)| {
+            let xs = x.saturating_sub(wh)..=(x + wh).min(map.width - 1);
+            let ys = y.saturating_sub(wh)..=(y + wh).min(map.height - 1);
+            ys.clone().all(|cy| {
+                xs.clone().all(|cx| {
+                    ranges.iter().any(|range| {
+                        (range.start().0..=range.start().1).contains(&cx)
+                            && (range.end().0..=range.end().1).contains(&cy)
+                    })
+                })
+            })
+        })
+    };
+    let mut map = ActivityMap::new(800, 400, 7);
+    map.clear();
+    // the last cell of the first tile and the first cell of the diagonal neighbour
+    map.insert(6, 6);
+    map.insert(7, 7);
+    assert_eq!(
+        map.changed_ranges(7),
+        vec![(0, 9)..=(0, 9), (4, 16)..=(4, 16)]
+    );
+    assert!(covers(&map, 7, &[(6, 6), (7, 7)]));
+    // the end of a row of tiles is not merged with the start of the next row
+    map.clear();
+    map.insert(799, 6);
+    map.insert(0, 7);
+    assert_eq!(
+        map.changed_ranges(7),
+        vec![(795, 799)..=(0, 9), (0, 9)..=(4, 16)]
+    );
+    assert!(covers(&map, 7, &[(799, 6), (0, 7)]));
+    // cells outside of the board are ignored
+    map.clear();
+    map.insert(800, 0);
+    map.insert(0, 400);
+    assert!(!map.has_changes());
+    // boards smaller than the kernel are a single tile
+    for (width, height, cw) in [(10, 5, 7), (5, 3, 7), (10, 5, 31), (50, 30, 31)] {
+        let mut map = ActivityMap::new(width, height, cw);
+        assert!(map.tile_size() <= width.max(height));
+        map.clear();
+        let changed = [(width - 1, height - 1), (0, height / 2)];
+        for (x, y) in changed {
+            map.insert(x, y);
+        }
+        assert!(covers(&map, cw, &changed), "{width}x{height}, {cw}");
+    }
+    let mut map = ActivityMap::new(5, 3, 7);
+    map.clear();
+    map.insert(4, 2);
+    assert_eq!(map.num_active(), 1);
+    assert_eq!(map.changed_ranges(7), vec![(0, 4)..=(0, 2)]);
+}
@@ -0,0 +1,46 @@
+use crate::{
+    color::{Color, ColorRamp, BLUE, RED, YELLOW},
+    CellType,
+};
+use matrices::{traits::Matrix, vec_matrix::VecMatrix};
+
+/// Number of generations every cell has been in its current state
+pub struct CellAge {
+    /// generation of the last transition of every cell
+    last_transition: VecMatrix<usize>,
+    pub color_ramp: ColorRamp,
+    /// cells of this age or older are drawn with the last color of the ramp
+    pub max_color_age: u32,
+}
+
+impl CellAge {
+    pub fn new(width: usize, height: usize) -> Self {
+        CellAge {
+            last_transition: VecMatrix::new(width, height, 0),
+            color_ramp: ColorRamp::new(vec![YELLOW, RED, BLUE]),
+            max_color_age: 100,
+        }
+    }
+
+    /// all cells start with age 0 in `generation`
+    pub fn reset(&mut self, width: usize, height: usize, generation: usize) {
+        self.last_transition = VecMatrix::new(width, height, generation);
+    }
+
+    pub fn transition(&mut self, ix: (usize, usize), generation: usize) {
+        self.last_transition.set_at_index(ix, generation);
+    }
+
+    pub fn age(&self, ix: (usize, usize), generation: usize) -> u32 {
+        generation.saturating_sub(self.last_transition.index(ix)) as u32
+    }
+
+    /// None for empty cells, they are drawn with their usual color
+    pub fn color(&self, ix: (usize, usize), cell: CellType, generation: usize) -> Option<Color> {
+        if cell == CellType::NoCell {
+            return None;
+        }
+        let t = self.age(ix, generation) as f32 / self.max_color_age.max(1) as f32;
+        Some(self.color_ramp.sample(t))
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{cell_type::CellType, FieldType, UiMode};
+use crate::{
+    cell_type::CellType, continuous::KernelGenerator, mask::Mask, reduction::Reduction,
+    topology::Topology, FieldType, UiMode,
+};
 use instant::{Duration, Instant};
 use matrices::traits::Symmetry;
 use std::ops::RangeInclusive;
@@ -15,6 +18,8 @@ pub struct AppConfig {
     pub randomize_range: RangeInclusive<CellType>,
     pub conv_matrix_copy_range: RangeInclusive<CellType>,
     pub conv_norms: [FieldType; 9],
+    /// how the neighbours are combined by every kernel
+    pub reductions: [Reduction; 9],
     pub clear_val: CellType,
     pub sym_editting: bool,
     pub symmetry: Symmetry,
@@ -25,7 +30,77 @@ pub struct AppConfig {
     pub draw_line_thickness: u8,
     pub bmirror_conv_kernels: bool,
     pub bdebug_window: bool,
-    pub bdebug_quad_tree: bool,
+    pub bdebug_activity: bool,
+    pub sim_mode: SimMode,
+    pub kernel_generator: KernelGenerator,
+    pub topology: Topology,
+    pub schedule: UpdateSchedule,
+    /// probability of a cell to be updated with `UpdateSchedule::Alpha`
+    pub update_probability: f32,
+    /// counts the generations every cell spent in its state, needed for rules with an age range
+    pub btrack_age: bool,
+    pub bcolor_by_age: bool,
+    /// painting changes the mask instead of the cells
+    pub paint_mask: Option<Mask>,
+    pub bshow_mask: bool,
+    /// `RugolState::clear` also removes the mask
+    pub bclear_mask: bool,
+    /// painting changes the region instead of the cells
+    pub paint_region: Option<u8>,
+    pub bshow_regions: bool,
+    /// region whose rules and kernels are edited
+    pub selected_region: usize,
+    /// part of the board which is copied into new stamp keyframes
+    pub stamp_origin: (usize, usize),
+    pub stamp_size: (usize, usize),
+    /// neighbour type of the interaction kernel shown in the kernel editor
+    pub interaction_neighbour: usize,
+    /// the discrete mode uses the `next` function of the rule script instead of the rules
+    pub bscript_rules: bool,
+    /// index into `RugolState::rule_registry`, the discrete mode uses this rule instead of the rules
+    pub update_rule: Option<usize>,
+    pub bshow_transition_graph: bool,
+    pub bshow_histogram: bool,
+    /// cell type whose accumulated values are shown in the histogram
+    pub histogram_cell_type: CellType,
+    pub histogram_bins: usize,
+    /// the range of the histogram is kept while an edge of a rule range is dragged
+    pub histogram_drag_range: Option<RangeInclusive<FieldType>>,
+    pub binspector: bool,
+    /// cell shown in the inspector, follows the mouse while it is over the board
+    pub inspect_ix: Option<(usize, usize)>,
+    /// every discrete step is compared with a full board reference step
+    pub bself_check: bool,
+}
+
+/// How the board is updated in `RugolState::step`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SimMode {
+    /// cells are one of the `CellType`s and change according to the rules
+    Discrete,
+    /// every cell holds a value in 0..=1 which is changed by a growth function
+    Continuous,
+    /// multiple continuous fields which diffuse and react with each other
+    ReactionDiffusion,
+    /// a single row evolves, every generation is drawn below the previous one
+    OneDimensional,
+    /// the board is split into 2×2 blocks which are rewritten through a lookup table
+    Margolus,
+    /// the rules are combined with the previous generation, the automaton is reversible
+    SecondOrder,
+}
+
+/// Which of the changed cells are updated in a step of `SimMode::Discrete`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateSchedule {
+    /// all cells are updated at the same time
+    Synchronous,
+    /// cells are updated one after another in random order and see the latest values
+    RandomSequential,
+    /// cells with an even x + y are updated in even generations, the others in odd generations
+    Checkerboard,
+    /// every cell is updated with `AppConfig::update_probability`
+    Alpha,
 }
 
 impl Default for AppConfig {
@@ -42,6 +117,7 @@ impl Default for AppConfig {
             randomize_range: CellType::NoCell..=CellType::A,
             conv_matrix_copy_range: CellType::NoCell..=CellType::A,
             conv_norms: [1.0; 9],
+            reductions: [Reduction::Sum; 9],
             clear_val: CellType::NoCell,
             sym_editting: false,
             symmetry: Symmetry::XY,
@@ -52,7 +128,33 @@ impl Default for AppConfig {
             draw_line_thickness: 1,
             bmirror_conv_kernels: true,
             bdebug_window: false,
-            bdebug_quad_tree: false,
+            bdebug_activity: false,
+            sim_mode: SimMode::Discrete,
+            kernel_generator: KernelGenerator::default(),
+            topology: Topology::Square,
+            schedule: UpdateSchedule::Synchronous,
+            update_probability: 0.5,
+            btrack_age: false,
+            bcolor_by_age: false,
+            paint_mask: None,
+            bshow_mask: true,
+            bclear_mask: false,
+            paint_region: None,
+            bshow_regions: true,
+            selected_region: 0,
+            stamp_origin: (0, 0),
+            stamp_size: (10, 10),
+            interaction_neighbour: 0,
+            bscript_rules: false,
+            update_rule: None,
+            bshow_transition_graph: false,
+            bshow_histogram: false,
+            histogram_cell_type: CellType::NoCell,
+            histogram_bins: 50,
+            histogram_drag_range: None,
+            binspector: false,
+            inspect_ix: None,
+            bself_check: false,
         }
     }
 }
@@ -69,6 +171,12 @@ pub struct UiSections {
     pub hover_edit_conv_matrix: bool,
     pub edit_colors: bool,
     pub hover_edit_colors: bool,
+    pub timeline: bool,
+    pub hover_timeline: bool,
+    pub script: bool,
+    pub hover_script: bool,
+    pub breakpoints: bool,
+    pub hover_breakpoints: bool,
     pub hover_preview: bool,
 }
 
@@ -88,4 +196,13 @@ impl UiSections {
     pub fn show_edit_colors(&self) -> bool {
         self.edit_colors || (self.hover_edit_colors && self.hover_preview)
     }
+    pub fn show_timeline(&self) -> bool {
+        self.timeline || (self.hover_timeline && self.hover_preview)
+    }
+    pub fn show_script(&self) -> bool {
+        self.script || (self.hover_script && self.hover_preview)
+    }
+    pub fn show_breakpoints(&self) -> bool {
+        self.breakpoints || (self.hover_breakpoints && self.hover_preview)
+    }
 }
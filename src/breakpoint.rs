@@ -0,0 +1,155 @@
+use crate::{cell_type::CellType, fire_counts::FireCounts};
+use matrices::{traits::Matrix, vec_matrix::VecMatrix};
+use std::fmt::Display;
+
+/// What a `Breakpoint` waits for
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Condition {
+    /// the cell at the position changes its state
+    CellChanges((usize, usize)),
+    /// the number of cells of a type goes from below the threshold to at least the threshold or back
+    PopulationCrosses(CellType, usize),
+    /// no cell changed in the step, only known in the discrete mode
+    NoChange,
+    /// the rule of the region fired at least once in the step
+    RuleFires { region: usize, rule: usize },
+    /// the generation is reached
+    Generation(usize),
+}
+
+impl Condition {
+    /// the value which is compared before and after a step
+    fn observe(&self, cells: &VecMatrix<CellType>) -> usize {
+        match *self {
+            Condition::CellChanges((x, y)) if x < cells.width() && y < cells.height() => {
+                cells.index((x, y)).as_index()
+            }
+            Condition::PopulationCrosses(cell_type, _) => (0..cells.height())
+                .flat_map(|y| (0..cells.width()).map(move |x| (x, y)))
+                .filter(|ix| cells.index(*ix) == cell_type)
+                .count(),
+            _ => 0,
+        }
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Condition::CellChanges(ix) => write!(f, "the cell {ix:?} changes"),
+            Condition::PopulationCrosses(cell_type, threshold) => {
+                write!(f, "the population of {cell_type} crosses {threshold}")
+            }
+            Condition::NoChange => write!(f, "no cell changed"),
+            Condition::RuleFires { region, rule } => {
+                write!(f, "rule {rule} of region {region} fired")
+            }
+            Condition::Generation(generation) => write!(f, "generation {generation} is reached"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Breakpoint {
+    pub condition: Condition,
+    pub benabled: bool,
+}
+
+/// What the breakpoints can see of a step
+pub struct StepResult<'a> {
+    pub generation: usize,
+    pub cells: &'a VecMatrix<CellType>,
+    /// false if no cell changed, true if cells changed or it is unknown
+    pub bchanged: bool,
+    pub fire_counts: &'a FireCounts,
+}
+
+/// Conditions which pause the simulation after a step
+#[derive(Default)]
+pub struct Breakpoints {
+    pub list: Vec<Breakpoint>,
+    /// the breakpoint which was hit last and the generation it was hit in
+    pub last_hit: Option<(usize, usize)>,
+    /// the observed value of every breakpoint before the step
+    before: Vec<usize>,
+}
+
+impl Breakpoints {
+    pub fn add(&mut self, condition: Condition) {
+        self.list.push(Breakpoint {
+            condition,
+            benabled: true,
+        });
+    }
+
+    /// has to be called before a step
+    pub fn before_step(&mut self, cells: &VecMatrix<CellType>) {
+        self.before.clear();
+        self.before.extend(self.list.iter().map(|breakpoint| {
+            if breakpoint.benabled {
+                breakpoint.condition.observe(cells)
+            } else {
+                0
+            }
+        }));
+    }
+
+    /// index of the first enabled breakpoint whose condition holds after the step
+    pub fn after_step(&mut self, step: &StepResult) -> Option<usize> {
+        let hit = self
+            .list
+            .iter()
+            .zip(&self.before)
+            .position(|(breakpoint, before)| {
+                if !breakpoint.benabled {
+                    return false;
+                }
+                let before = *before;
+                let condition = breakpoint.condition;
+                match condition {
+                    Condition::CellChanges(_) => condition.observe(step.cells) != before,
+                    Condition::PopulationCrosses(_, threshold) => {
+                        (before < threshold) != (condition.observe(step.cells) < threshold)
+                    }
+                    Condition::NoChange => !step.bchanged,
+                    Condition::RuleFires { region, rule } => step
+                        .fire_counts
+                        .last(region)
+                        .get(rule)
+                        .is_some_and(|count| *count > 0),
+                    Condition::Generation(generation) => step.generation == generation,
+                }
+            });
+        if let Some(ix) = hit {
+            self.last_hit = Some((ix, step.generation));
+        }
+        hit
+    }
+}
+
+#[test]
+fn cell_and_population_breakpoints() {
+    let mut breakpoints = Breakpoints::default();
+    breakpoints.add(Condition::PopulationCrosses(CellType::A, 2));
+    breakpoints.add(Condition::CellChanges((1, 0)));
+    let mut cells = VecMatrix::new(3, 1, CellType::NoCell);
+    cells.set_at_index((0, 0), CellType::A);
+    let fire_counts = FireCounts::default();
+    let after_step = |breakpoints: &mut Breakpoints, cells: &VecMatrix<CellType>, generation| {
+        breakpoints.after_step(&StepResult {
+            generation,
+            cells,
+            bchanged: true,
+            fire_counts: &fire_counts,
+        })
+    };
+    breakpoints.before_step(&cells);
+    cells.set_at_index((2, 0), CellType::A);
+    assert_eq!(after_step(&mut breakpoints, &cells, 1), Some(0));
+    breakpoints.before_step(&cells);
+    cells.set_at_index((1, 0), CellType::A);
+    assert_eq!(after_step(&mut breakpoints, &cells, 2), Some(1));
+    breakpoints.before_step(&cells);
+    assert_eq!(after_step(&mut breakpoints, &cells, 3), None);
+    assert_eq!(breakpoints.last_hit, Some((1, 2)));
+}
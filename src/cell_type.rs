@@ -208,6 +208,12 @@ impl CellTypeMap {
         self.map[self.get_selected_idx()].1
     }
 
+    pub fn set_value(&mut self, cell_type: CellType, value: FieldType) {
+        if let Some(entry) = self.map.get_mut(cell_type.as_index()) {
+            entry.1 = value;
+        }
+    }
+
     pub fn edit(&mut self, ui: &mut Ui) -> Vec<CellType> {
         let mut selected_idx = self.get_selected_idx();
         let mut changed = Vec::new();
@@ -90,6 +90,46 @@ impl Color {
     }
 }
 
+/// Maps values in 0..=1 to colors by interpolating linearly between evenly spaced stops
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ColorRamp {
+    pub stops: Vec<Color>,
+}
+
+impl ColorRamp {
+    pub fn new(stops: Vec<Color>) -> Self {
+        ColorRamp { stops }
+    }
+
+    pub fn sample(&self, t: f32) -> Color {
+        match self.stops.len() {
+            0 => BLACK,
+            1 => self.stops[0],
+            len => {
+                let pos = t.clamp(0.0, 1.0) * (len - 1) as f32;
+                let ix = (pos as usize).min(len - 2);
+                let frac = pos - ix as f32;
+                let from: [f32; 4] = self.stops[ix].into();
+                let to: [f32; 4] = self.stops[ix + 1].into();
+                let mut res = [0.0; 4];
+                for c in 0..4 {
+                    res[c] = from[c] * (1.0 - frac) + to[c] * frac;
+                }
+                res.into()
+            }
+        }
+    }
+}
+
+#[test]
+fn color_ramp_sample() {
+    let ramp = ColorRamp::new(vec![BLACK, WHITE]);
+    assert_eq!(ramp.sample(0.0), BLACK);
+    assert_eq!(ramp.sample(1.0), WHITE);
+    assert_eq!(ramp.sample(2.0), WHITE);
+    assert_eq!(ramp.sample(0.5), Color::new(0.5, 0.5, 0.5, 1.0));
+}
+
 pub mod colors {
     //! Constants for some common colors.
 
@@ -0,0 +1,177 @@
+use crate::{
+    color::{ColorRamp, BLACK, DARKPURPLE, RED, WHITE, YELLOW},
+    FieldType,
+};
+use matrices::traits::Matrix;
+use serde::{Deserialize, Serialize};
+
+/// Maps the accumulated value of a cell to a growth rate in -1..=1
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GrowthFunction {
+    Gaussian,
+    Step,
+    Polynomial,
+}
+
+/// Parameters of the continuous (Lenia like) simulation mode
+/// every cell holds a value in 0..=1 and is updated by `x += dt * growth(kernel * field)`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ContinuousParams {
+    pub growth: GrowthFunction,
+    /// accumulated value with the highest growth rate
+    pub mu: FieldType,
+    /// width of the growth function
+    pub sigma: FieldType,
+    pub dt: FieldType,
+    pub color_ramp: ColorRamp,
+}
+
+impl Default for ContinuousParams {
+    fn default() -> Self {
+        ContinuousParams {
+            growth: GrowthFunction::Gaussian,
+            mu: 0.15,
+            sigma: 0.015,
+            dt: 0.1,
+            color_ramp: ColorRamp::new(vec![BLACK, DARKPURPLE, RED, YELLOW, WHITE]),
+        }
+    }
+}
+
+impl ContinuousParams {
+    pub fn growth(&self, acc: FieldType) -> FieldType {
+        let d = acc - self.mu;
+        match self.growth {
+            GrowthFunction::Gaussian => {
+                2.0 * (-(d * d) / (2.0 * self.sigma * self.sigma)).exp() - 1.0
+            }
+            GrowthFunction::Step => {
+                if d.abs() <= self.sigma {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            GrowthFunction::Polynomial => {
+                2.0 * (1.0 - (d * d) / (9.0 * self.sigma * self.sigma))
+                    .max(0.0)
+                    .powi(4)
+                    - 1.0
+            }
+        }
+    }
+
+    /// new value of a cell with value `value` and accumulated value `acc`
+    pub fn update(&self, value: FieldType, acc: FieldType) -> FieldType {
+        (value + self.dt * self.growth(acc)).clamp(0.0, 1.0)
+    }
+}
+
+/// Settings for the kernel generators
+pub struct KernelGenerator {
+    /// distance of the ring from the center relative to the kernel radius
+    pub ring_center: FieldType,
+    /// width of the ring relative to the kernel radius
+    pub ring_width: FieldType,
+    /// standard deviation of the gaussian kernel relative to the kernel radius
+    pub gauss_sigma: FieldType,
+}
+
+impl Default for KernelGenerator {
+    fn default() -> Self {
+        KernelGenerator {
+            ring_center: 0.5,
+            ring_width: 0.15,
+            gauss_sigma: 0.5,
+        }
+    }
+}
+
+impl KernelGenerator {
+    pub fn ring<C: Matrix<FieldType>>(&self, kernel: &mut C) {
+        let (center, width) = (self.ring_center, self.ring_width);
+        fill_radial(kernel, |r| (-((r - center) / width).powi(2) / 2.0).exp());
+    }
+
+    pub fn gaussian<C: Matrix<FieldType>>(&self, kernel: &mut C) {
+        let sigma = self.gauss_sigma;
+        fill_radial(kernel, |r| (-(r * r) / (2.0 * sigma * sigma)).exp());
+    }
+}
+
+/// fills `kernel` with f(distance to the center / kernel radius) and normalizes it to a sum of 1
+fn fill_radial<C: Matrix<FieldType>, F: Fn(FieldType) -> FieldType>(kernel: &mut C, f: F) {
+    let w = kernel.width();
+    let h = kernel.height();
+    let radius = (w.min(h) / 2) as FieldType + 0.5;
+    let mut sum = 0.0;
+    for x in 0..w {
+        for y in 0..h {
+            let dx = x as FieldType - (w / 2) as FieldType;
+            let dy = y as FieldType - (h / 2) as FieldType;
+            let r = (dx * dx + dy * dy).sqrt() / radius;
+            let val = if r < 1.0 { f(r) } else { 0.0 };
+            sum += val;
+            kernel.set_at_index((x, y), val);
+        }
+    }
+    if sum > 0.0 {
+        for x in 0..w {
+            for y in 0..h {
+                kernel.set_at_index((x, y), kernel.index((x, y)) / sum);
+            }
+        }
+    }
+}
+
+#[test]
+fn growth_peaks_at_mu() {
+    for growth in [
+        GrowthFunction::Gaussian,
+        GrowthFunction::Step,
+        GrowthFunction::Polynomial,
+    ] {
+        let params = ContinuousParams {
+            growth,
+            ..Default::default()
+        };
+        assert_eq!(params.growth(params.mu), 1.0, "{growth:?}");
+        assert!(
+            params.growth(params.mu + 0.5 * params.sigma) > 0.0,
+            "{growth:?}"
+        );
+        assert_eq!(params.growth(params.mu + 1.0), -1.0, "{growth:?}");
+        assert_eq!(params.growth(params.mu - 1.0), -1.0, "{growth:?}");
+        // the value stays in 0..=1
+        assert_eq!(params.update(0.99, params.mu), 1.0);
+        assert_eq!(params.update(0.01, params.mu + 1.0), 0.0);
+        assert!((params.update(0.5, params.mu) - (0.5 + params.dt)).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn generated_kernels_sum_to_one() {
+    use matrices::vec_matrix::VecMatrix;
+    let generator = KernelGenerator::default();
+    for size in [3, 7, 31] {
+        let mut ring = VecMatrix::new(size, size, 0.0);
+        generator.ring(&mut ring);
+        let mut gaussian = VecMatrix::new(size, size, 0.0);
+        generator.gaussian(&mut gaussian);
+        for kernel in [&ring, &gaussian] {
+            let values = (0..size).flat_map(|y| (0..size).map(move |x| kernel.index((x, y))));
+            let sum: FieldType = values.clone().sum();
+            assert!((sum - 1.0).abs() < 1e-5, "{size}: {sum}");
+            assert!(values.clone().all(|value| value >= 0.0));
+        }
+        // the gaussian is highest in the center, the ring away from it
+        let c = size / 2;
+        assert!(gaussian.index((c, c)) >= gaussian.index((c, 0)));
+        if size > 3 {
+            assert!(ring.index((c, c)) < ring.index((c, c / 2)));
+            // the corners are outside of the radius
+            assert_eq!(ring.index((0, 0)), 0.0);
+            assert_eq!(gaussian.index((0, 0)), 0.0);
+        }
+    }
+}
@@ -1,10 +1,42 @@
 use super::traits::ConvolutionT;
-use crate::{index_set::IndexSet, CellType};
+use crate::{
+    index_set::IndexSet,
+    interaction::Interaction,
+    reduction::{Reducer, Reduction},
+    topology::Topology,
+    CellType,
+};
 use matrices::traits::*;
 use matrices::vec_matrix::VecMatrix;
 use num_traits::{AsPrimitive, One, Zero};
 use quad_rand::{gen_range, RandomRange};
-use std::ops::{AddAssign, Mul};
+use std::ops::{Div, Range};
+
+/// Selects the kernel of every cell in a convolution
+pub struct KernelParams<'a, T, Conv> {
+    pub kernels: &'a [Conv],
+    /// every cell uses `kernels[0]` instead of the kernel of its type
+    pub bsingle_kernel: bool,
+    pub interaction: Option<&'a Interaction<T, Conv>>,
+    /// reduction of every kernel, indexed like `kernels`
+    pub reductions: &'a [Reduction],
+    /// kernels are mirrored vertically for the cells flipped by the topology
+    pub topology: Topology,
+    /// cells outside of the board keep the value the field was created with and take part,
+    /// otherwise they are left out; with interaction they have no type and are always left out
+    pub bfixed_border: bool,
+}
+
+/// The kernel of one cell, the same for all of its neighbours
+struct CellKernel<'a, Conv> {
+    centre: usize,
+    kernel_ix: usize,
+    kernel: &'a Conv,
+    bflipped: bool,
+    /// positions of the kernel window which take part in the convolution
+    kx: Range<usize>,
+    ky: Range<usize>,
+}
 
 // the kernel should be a square matrix
 #[derive(Debug, Clone)]
@@ -15,7 +47,7 @@ pub struct Convolution<T: Copy + Clone, const KW: usize> {
 }
 
 impl<
-        T: Copy + Zero + Mul + AddAssign + Mul<Output = T>,
+        T: Copy + Zero + One + PartialOrd + Div<Output = T>,
         Conv: Matrix<T>,
         Acc: Matrix<T>,
         const KW: usize,
@@ -23,26 +55,198 @@ impl<
 {
     fn convolution(
         &self,
-        kernels: &[Conv],
-        single_kernel: bool,
+        params: &KernelParams<T, Conv>,
         cell_type_matrix: &VecMatrix<CellType>,
         acc_matrix: &mut Acc,
         indices: &IndexSet,
     ) {
-        for (ixx, ixy) in indices.iter() {
-            let mut acc: T = Zero::zero();
-            let kernel_ix = cell_type_matrix.index((ixx, ixy)).as_index();
-            let slice = &self.base[ixx + self.width * ixy];
-            for kixx in 0..KW {
-                for kixy in 0..KW {
-                    if single_kernel {
-                        acc += slice[kixy * KW + kixx] * kernels[0].index((kixx, kixy));
-                    } else {
-                        acc += slice[kixy * KW + kixx] * kernels[kernel_ix].index((kixx, kixy));
-                    }
+        if Self::is_weighted_sum(params) {
+            // decided once for all cells, the plain sum is the hot loop of the simulation
+            for ix in indices.iter() {
+                let cell = self.cell_kernel(params, cell_type_matrix, ix);
+                acc_matrix.set_at_index(ix, self.weighted_sum(&cell, ix));
+            }
+            return;
+        }
+        let mut reducers = Reducer::all(params.reductions);
+        for ix in indices.iter() {
+            let acc = self.convolution_at(params, cell_type_matrix, ix, &mut reducers);
+            acc_matrix.set_at_index(ix, acc);
+        }
+    }
+}
+
+impl<T: Copy + Zero + One + PartialOrd + Div<Output = T>, const KW: usize> Convolution<T, KW> {
+    /// accumulated value of a single cell
+    /// `reducers` has a reducer for every reduction of `params`, see `Reducer::all`
+    #[inline]
+    pub fn convolution_at<Conv: Matrix<T>>(
+        &self,
+        params: &KernelParams<T, Conv>,
+        cell_type_matrix: &VecMatrix<CellType>,
+        ix: (usize, usize),
+        reducers: &mut [Reducer<T>],
+    ) -> T {
+        let cell = self.cell_kernel(params, cell_type_matrix, ix);
+        if Self::is_weighted_sum(params) {
+            return self.weighted_sum(&cell, ix);
+        }
+        let reducer = &mut reducers[cell.kernel_ix];
+        self.for_each_contribution(params, cell_type_matrix, ix, &cell, |_, value, weight| {
+            reducer.add(value, weight)
+        });
+        reducer.finish()
+    }
+
+    /// the value and the weight of every neighbour of a cell which takes part in the convolution
+    /// `(kixx, kixy)` is the position in the kernel window, the cell itself is at `(KW / 2, KW / 2)`
+    pub fn contributions_at<Conv: Matrix<T>>(
+        &self,
+        params: &KernelParams<T, Conv>,
+        cell_type_matrix: &VecMatrix<CellType>,
+        ix: (usize, usize),
+    ) -> Vec<((usize, usize), T, T)> {
+        let cell = self.cell_kernel(params, cell_type_matrix, ix);
+        let mut res = Vec::new();
+        self.for_each_contribution(params, cell_type_matrix, ix, &cell, |kix, value, weight| {
+            res.push((kix, value, weight))
+        });
+        res
+    }
+
+    /// the weight of the neighbour at `(kixx, kixy)` in the kernel window of the cell at `ix`,
+    /// None if the neighbour does not take part in the convolution
+    pub fn weight_at<Conv: Matrix<T>>(
+        &self,
+        params: &KernelParams<T, Conv>,
+        cell_type_matrix: &VecMatrix<CellType>,
+        ix: (usize, usize),
+        (kixx, kixy): (usize, usize),
+    ) -> Option<T> {
+        let cell = self.cell_kernel(params, cell_type_matrix, ix);
+        (cell.kx.contains(&kixx) && cell.ky.contains(&kixy))
+            .then(|| Self::weight(params, cell_type_matrix, ix, &cell, (kixx, kixy)))
+    }
+
+    fn for_each_contribution<Conv: Matrix<T>>(
+        &self,
+        params: &KernelParams<T, Conv>,
+        cell_type_matrix: &VecMatrix<CellType>,
+        (ixx, ixy): (usize, usize),
+        cell: &CellKernel<Conv>,
+        mut f: impl FnMut((usize, usize), T, T),
+    ) {
+        let slice = &self.base[ixx + self.width * ixy];
+        for kixx in cell.kx.clone() {
+            for kixy in cell.ky.clone() {
+                let weight = Self::weight(params, cell_type_matrix, (ixx, ixy), cell, (kixx, kixy));
+                f((kixx, kixy), slice[kixy * KW + kixx], weight);
+            }
+        }
+    }
+
+    #[inline]
+    fn cell_kernel<'a, Conv: Matrix<T>>(
+        &self,
+        params: &KernelParams<'a, T, Conv>,
+        cell_type_matrix: &VecMatrix<CellType>,
+        (ixx, ixy): (usize, usize),
+    ) -> CellKernel<'a, Conv> {
+        let centre = cell_type_matrix.index((ixx, ixy)).as_index();
+        let kernel_ix = if params.bsingle_kernel { 0 } else { centre };
+        let wh = KW / 2;
+        let (kx, ky) = if params.bfixed_border && params.interaction.is_none() {
+            (0..KW, 0..KW)
+        } else {
+            // the window is clipped to the board
+            (
+                wh.saturating_sub(ixx)..KW.min(self.width + wh - ixx),
+                wh.saturating_sub(ixy)..KW.min(self.height + wh - ixy),
+            )
+        };
+        CellKernel {
+            centre,
+            kernel_ix,
+            kernel: &params.kernels[kernel_ix],
+            bflipped: params.topology.is_flipped((ixx, ixy)),
+            kx,
+            ky,
+        }
+    }
+
+    /// true if every neighbour contributes `value * weight` to a sum, the common case
+    fn is_weighted_sum<Conv>(params: &KernelParams<T, Conv>) -> bool {
+        params.interaction.is_none()
+            && params
+                .reductions
+                .iter()
+                .all(|reduction| *reduction == Reduction::Sum)
+    }
+
+    /// the convolution without interaction and with `Reduction::Sum`
+    #[inline]
+    fn weighted_sum<Conv: Matrix<T>>(
+        &self,
+        cell: &CellKernel<Conv>,
+        (ixx, ixy): (usize, usize),
+    ) -> T {
+        let slice = &self.base[ixx + self.width * ixy];
+        if cell.kx.len() == KW && cell.ky.len() == KW {
+            // constant bounds for the cells away from the border
+            Self::weighted_sum_in(slice, cell, 0..KW, 0..KW)
+        } else {
+            Self::weighted_sum_in(slice, cell, cell.kx.clone(), cell.ky.clone())
+        }
+    }
+
+    #[inline(always)]
+    fn weighted_sum_in<Conv: Matrix<T>>(
+        slice: &[T],
+        cell: &CellKernel<Conv>,
+        kx: Range<usize>,
+        ky: Range<usize>,
+    ) -> T {
+        let mut acc = T::zero();
+        // the flip is decided outside of the loops
+        if cell.bflipped {
+            for kixx in kx {
+                for kixy in ky.clone() {
+                    acc = acc + slice[kixy * KW + kixx] * cell.kernel.index((kixx, KW - 1 - kixy));
+                }
+            }
+        } else {
+            for kixx in kx {
+                for kixy in ky.clone() {
+                    acc = acc + slice[kixy * KW + kixx] * cell.kernel.index((kixx, kixy));
                 }
             }
-            acc_matrix.set_at_index((ixx, ixy), acc);
+        }
+        acc
+    }
+
+    /// `(kixx, kixy)` has to be in the window of `cell`
+    #[inline]
+    fn weight<Conv: Matrix<T>>(
+        params: &KernelParams<T, Conv>,
+        cell_type_matrix: &VecMatrix<CellType>,
+        (ixx, ixy): (usize, usize),
+        cell: &CellKernel<Conv>,
+        (kixx, kixy): (usize, usize),
+    ) -> T {
+        let kernel_y = if cell.bflipped { KW - 1 - kixy } else { kixy };
+        let neighbour = || {
+            cell_type_matrix
+                .index((ixx + kixx - KW / 2, ixy + kixy - KW / 2))
+                .as_index()
+        };
+        match params.interaction {
+            None => cell.kernel.index((kixx, kernel_y)),
+            Some(Interaction::Weights(weights)) => {
+                cell.kernel.index((kixx, kernel_y)) * weights[cell.centre][neighbour()]
+            }
+            Some(Interaction::Kernels(kernels)) => {
+                kernels[cell.centre][neighbour()].index((kixx, kernel_y))
+            }
         }
     }
 }
@@ -187,3 +391,62 @@ impl<T: Copy + ToString, const KW: usize> Convolution<T, KW> {
         res
     }
 }
+
+#[test]
+fn border_is_the_same_with_and_without_interaction() {
+    use matrices::const_matrix::ConstMatrix;
+    let kernel: ConstMatrix<f32, 3, 3> = ConstMatrix::new_std_conv_matrix(3, 3);
+    let kernels = [kernel; 9];
+    let reductions = [Reduction::Min; 9];
+    // the field outside of the board must not matter
+    let mut fields: Convolution<f32, 3> = Convolution::new(4, 3, -1.0);
+    let cells = VecMatrix::new(4, 3, CellType::A);
+    for ixy in 0..3 {
+        for ixx in 0..4 {
+            fields.set_at_index((ixx, ixy), (1 + ixx + ixy) as f32);
+        }
+    }
+    let neutral = Interaction::neutral_weights();
+    let params = |interaction| KernelParams {
+        kernels: &kernels,
+        bsingle_kernel: true,
+        interaction,
+        reductions: &reductions,
+        topology: Topology::Square,
+        bfixed_border: false,
+    };
+    for ix in [(0, 0), (3, 0), (3, 2), (1, 1)] {
+        let mut reducers = Reducer::all(&reductions);
+        let plain = fields.convolution_at(&params(None), &cells, ix, &mut reducers);
+        let weighted = fields.convolution_at(&params(Some(&neutral)), &cells, ix, &mut reducers);
+        assert_eq!(plain, weighted, "{ix:?}");
+        assert!(plain >= 1.0, "{ix:?}");
+    }
+}
+
+#[test]
+fn fixed_border_keeps_the_field_value_outside() {
+    use matrices::const_matrix::ConstMatrix;
+    let mut kernel: ConstMatrix<f32, 3, 3> = ConstMatrix::new(3, 3, 0.0);
+    crate::reaction_diffusion::laplacian_kernel(&mut kernel);
+    let kernels = [kernel];
+    let reductions = [Reduction::Sum];
+    // a constant field does not diffuse, also not at the border
+    let fields: Convolution<f32, 3> = Convolution::new(4, 3, 1.0);
+    let cells = VecMatrix::new(4, 3, CellType::A);
+    let params = |bfixed_border| KernelParams {
+        kernels: &kernels,
+        bsingle_kernel: true,
+        interaction: None,
+        reductions: &reductions,
+        topology: Topology::Square,
+        bfixed_border,
+    };
+    let mut reducers = Reducer::all(&reductions);
+    for ix in [(0, 0), (3, 1), (1, 1)] {
+        let diffused = fields.convolution_at(&params(true), &cells, ix, &mut reducers);
+        assert!(diffused.abs() < 1e-6, "{ix:?}: {diffused}");
+    }
+    let absorbed = fields.convolution_at(&params(false), &cells, (0, 0), &mut reducers);
+    assert!(absorbed < -0.1, "{absorbed}");
+}
@@ -0,0 +1,192 @@
+use crate::{mask::Mask, CellType};
+use matrices::{traits::Matrix, vec_matrix::VecMatrix};
+use quad_rand::gen_range;
+use serde::{Deserialize, Serialize};
+
+/// Largest rule table that can be edited by hand
+pub const MAX_EDITABLE_TABLE_LEN: usize = 512;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OneDimRuleKind {
+    /// 2 states, radius 1, the rule number encodes the rule table
+    Wolfram,
+    /// the new state depends on the sum of the neighbourhood
+    Totalistic,
+    /// the new state depends on every single cell of the neighbourhood
+    General,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct OneDimRule {
+    pub kind: OneDimRuleKind,
+    pub wolfram: u8,
+    /// number of states for `Totalistic` and `General` rules
+    pub states: usize,
+    /// the neighbourhood is 2 * radius + 1 cells wide
+    pub radius: usize,
+    /// new state indexed by the sum of the neighbourhood
+    pub totalistic: Vec<CellType>,
+    /// new state indexed by the neighbourhood read as a number in base `states`
+    /// the left most cell is the most significant digit
+    pub table: Vec<CellType>,
+}
+
+impl Default for OneDimRule {
+    fn default() -> Self {
+        let mut res = OneDimRule {
+            kind: OneDimRuleKind::Wolfram,
+            wolfram: 90,
+            states: 2,
+            radius: 1,
+            totalistic: Vec::new(),
+            table: Vec::new(),
+        };
+        res.resize_tables();
+        res
+    }
+}
+
+impl OneDimRule {
+    pub fn totalistic_len(&self) -> usize {
+        (2 * self.radius + 1) * (self.states - 1) + 1
+    }
+
+    pub fn table_len(&self) -> usize {
+        self.states.pow(2 * self.radius as u32 + 1)
+    }
+
+    /// has to be called after `states` or `radius` changed
+    pub fn resize_tables(&mut self) {
+        self.states = self.states.clamp(2, 9);
+        self.totalistic
+            .resize(self.totalistic_len(), CellType::NoCell);
+        self.table.resize(self.table_len(), CellType::NoCell);
+    }
+
+    pub fn randomize_table(&mut self) {
+        let max_state = CellType::try_from(self.states - 1).unwrap_or(CellType::H);
+        for cell in self.table.iter_mut() {
+            *cell = CellType::random_range(&(CellType::NoCell..=max_state));
+        }
+    }
+
+    /// computes the next generation of `row` with periodic boundaries
+    pub fn next_row(&self, row: &[CellType], next: &mut [CellType]) {
+        let (states, radius) = match self.kind {
+            OneDimRuleKind::Wolfram => (2, 1),
+            _ => (self.states, self.radius),
+        };
+        let w = row.len();
+        for (ix, next_cell) in next.iter_mut().enumerate() {
+            let neighbourhood = (0..(2 * radius + 1))
+                .map(|offset| row[(ix + w * radius + offset - radius) % w].as_index() % states);
+            *next_cell = match self.kind {
+                OneDimRuleKind::Wolfram => {
+                    let n = neighbourhood.fold(0, |acc, state| acc * 2 + state);
+                    if (self.wolfram >> n) & 1 == 1 {
+                        CellType::A
+                    } else {
+                        CellType::NoCell
+                    }
+                }
+                OneDimRuleKind::Totalistic => self.totalistic[neighbourhood.sum::<usize>()],
+                OneDimRuleKind::General => {
+                    self.table[neighbourhood.fold(0, |acc, state| acc * states + state)]
+                }
+            };
+        }
+    }
+}
+
+/// One dimensional automaton which draws every generation as the next row of the board
+#[derive(Default)]
+pub struct OneDim {
+    pub rule: OneDimRule,
+    /// row of the board which holds the newest generation
+    pub row: usize,
+}
+
+impl OneDim {
+    /// masked cells keep their state, they are neither scrolled nor overwritten
+    pub fn step(&mut self, cells: &mut VecMatrix<CellType>, mask: &VecMatrix<Mask>) {
+        let w = cells.width();
+        let h = cells.height();
+        self.row = self.row.min(h - 1);
+        let current: Vec<CellType> = (0..w).map(|ixx| cells.index((ixx, self.row))).collect();
+        let mut next = vec![CellType::NoCell; w];
+        self.rule.next_row(&current, &mut next);
+        if self.row + 1 < h {
+            self.row += 1;
+        } else {
+            // the board is full, scroll up by one row
+            for ixy in 1..h {
+                for ixx in (0..w).filter(|ixx| mask.index((*ixx, ixy - 1)).is_open()) {
+                    cells.set_at_index((ixx, ixy - 1), cells.index((ixx, ixy)));
+                }
+            }
+        }
+        for (ixx, cell) in next.into_iter().enumerate() {
+            if mask.index((ixx, self.row)).is_open() {
+                cells.set_at_index((ixx, self.row), cell);
+            }
+        }
+    }
+
+    /// clears the board and places a single `CellType::A` in the middle of the first row
+    pub fn seed_single(&mut self, cells: &mut VecMatrix<CellType>) {
+        cells.clear(CellType::NoCell);
+        cells.set_at_index((cells.width() / 2, 0), CellType::A);
+        self.row = 0;
+    }
+
+    /// clears the board and fills the first row with random states
+    pub fn seed_random(&mut self, cells: &mut VecMatrix<CellType>) {
+        let states = match self.rule.kind {
+            OneDimRuleKind::Wolfram => 2,
+            _ => self.rule.states,
+        };
+        cells.clear(CellType::NoCell);
+        for ixx in 0..cells.width() {
+            let state = CellType::try_from(gen_range(0, states)).unwrap_or(CellType::NoCell);
+            cells.set_at_index((ixx, 0), state);
+        }
+        self.row = 0;
+    }
+}
+
+#[test]
+fn wolfram_rule_90() {
+    use CellType::{NoCell as O, A};
+    let rule = OneDimRule::default();
+    let row = [O, O, O, A, O, O, O];
+    let mut next = [O; 7];
+    rule.next_row(&row, &mut next);
+    assert_eq!(next, [O, O, A, O, A, O, O]);
+    let row = next;
+    rule.next_row(&row, &mut next);
+    assert_eq!(next, [O, A, O, O, O, A, O]);
+}
+
+#[test]
+fn masked_cells_keep_their_state() {
+    use CellType::{NoCell as O, A, B};
+    let mut one_dim = OneDim::default();
+    let mut cells = VecMatrix::new(7, 2, O);
+    let mut mask = VecMatrix::new(7, 2, Mask::Open);
+    cells.set_at_index((3, 0), A);
+    cells.set_at_index((2, 1), B);
+    cells.set_at_index((5, 0), B);
+    mask.set_at_index((2, 1), Mask::Wall);
+    mask.set_at_index((5, 0), Mask::Wall);
+    one_dim.step(&mut cells, &mask);
+    let row = |cells: &VecMatrix<CellType>, ixy| {
+        (0..7)
+            .map(|ixx| cells.index((ixx, ixy)))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(row(&cells, 1), [O, O, B, O, A, O, O]);
+    // the board is full, the new row scrolls up past the wall at (5, 0)
+    one_dim.step(&mut cells, &mask);
+    assert_eq!(row(&cells, 0), [O, O, B, O, A, B, O]);
+    assert_eq!(row(&cells, 1), [O, O, B, A, O, A, O]);
+}
@@ -0,0 +1,85 @@
+/// How often the rules of every region fired among the active cells
+/// only cells which are evaluated in a step are counted,
+/// cells with an unchanged neighbourhood are skipped by the activity map,
+/// so a count of 0 does not mean that no cell matches the rule
+#[derive(Default)]
+pub struct FireCounts {
+    /// `[region][rule]` in the last generation
+    last: Vec<Vec<usize>>,
+    /// `[region][rule]` since the counters were reset
+    total: Vec<Vec<usize>>,
+    /// generations since the counters were reset
+    generations: usize,
+}
+
+impl FireCounts {
+    /// has to be called before a step, `num_rules` is the number of rules of every region
+    pub fn start_generation(&mut self, num_rules: impl Iterator<Item = usize>) {
+        self.last.clear();
+        self.last.extend(num_rules.map(|len| vec![0; len]));
+        self.total.resize(self.last.len(), Vec::new());
+        for (total, last) in self.total.iter_mut().zip(self.last.iter()) {
+            total.resize(last.len(), 0);
+        }
+        self.generations += 1;
+    }
+
+    /// regions which do not exist count as region 0
+    pub fn fire(&mut self, region: usize, rule: usize) {
+        let region = if region < self.last.len() { region } else { 0 };
+        for counts in [&mut self.last, &mut self.total] {
+            if let Some(count) = counts
+                .get_mut(region)
+                .and_then(|counts| counts.get_mut(rule))
+            {
+                *count += 1;
+            }
+        }
+    }
+
+    /// has to be called when rules are edited, added, removed or moved,
+    /// otherwise the counts belong to the wrong rules
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// counts of the rules of `region` in the last generation
+    pub fn last(&self, region: usize) -> &[usize] {
+        self.last.get(region).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// counts of the rules of `region` since the counters were reset
+    pub fn total(&self, region: usize) -> &[usize] {
+        self.total.get(region).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn generations(&self) -> usize {
+        self.generations
+    }
+
+    /// the fraction of the rules of `region` which fired at least once
+    pub fn coverage(&self, region: usize) -> f32 {
+        let total = self.total(region);
+        if total.is_empty() {
+            return 0.0;
+        }
+        total.iter().filter(|count| **count > 0).count() as f32 / total.len() as f32
+    }
+}
+
+#[test]
+fn counts_per_generation_and_total() {
+    let mut counts = FireCounts::default();
+    counts.start_generation([3, 1].into_iter());
+    counts.fire(0, 0);
+    counts.fire(0, 0);
+    counts.fire(1, 0);
+    counts.fire(5, 2);
+    counts.start_generation([3, 1].into_iter());
+    counts.fire(0, 0);
+    assert_eq!(counts.last(0), &[1, 0, 0]);
+    assert_eq!(counts.total(0), &[3, 0, 1]);
+    assert_eq!(counts.total(1), &[1]);
+    assert_eq!(counts.generations(), 2);
+    assert_eq!(counts.coverage(0), 2.0 / 3.0);
+}
@@ -0,0 +1,68 @@
+use crate::FieldType;
+use std::ops::RangeInclusive;
+
+/// Counts of values in equally wide bins between `range.start()` and `range.end()`
+pub struct Histogram {
+    pub range: RangeInclusive<FieldType>,
+    pub bins: Vec<usize>,
+}
+
+impl Histogram {
+    /// the range covers all values with some space on both sides
+    pub fn new(values: &[FieldType], num_bins: usize) -> Self {
+        let (min, max) = values
+            .iter()
+            .copied()
+            .filter(|value| value.is_finite())
+            .fold((FieldType::MAX, FieldType::MIN), |(min, max), value| {
+                (min.min(value), max.max(value))
+            });
+        let (min, max) = if min > max { (0.0, 0.0) } else { (min, max) };
+        let padding = (0.1 * (max - min)).max(0.5);
+        Self::with_range(values, num_bins, min - padding..=max + padding)
+    }
+
+    /// values outside of `range` are counted in the first or the last bin
+    pub fn with_range(
+        values: &[FieldType],
+        num_bins: usize,
+        range: RangeInclusive<FieldType>,
+    ) -> Self {
+        let mut res = Histogram {
+            range,
+            bins: vec![0; num_bins.max(1)],
+        };
+        for value in values.iter().filter(|value| value.is_finite()) {
+            let bin = res.bin(*value);
+            res.bins[bin] += 1;
+        }
+        res
+    }
+
+    pub fn span(&self) -> FieldType {
+        self.range.end() - self.range.start()
+    }
+
+    /// the bin of `value`, values outside of the range are put into the first or the last bin
+    pub fn bin(&self, value: FieldType) -> usize {
+        let t = (value - self.range.start()) / self.span();
+        ((t * self.bins.len() as FieldType) as usize).min(self.bins.len() - 1)
+    }
+
+    /// the range of values counted in `bin`
+    pub fn bin_range(&self, bin: usize) -> RangeInclusive<FieldType> {
+        let width = self.span() / self.bins.len() as FieldType;
+        let start = self.range.start() + bin as FieldType * width;
+        start..=start + width
+    }
+}
+
+#[test]
+fn values_in_bins() {
+    let histogram = Histogram::new(&[0.0, 1.0, 1.0, 2.0, 2.0, 2.0, 3.0], 4);
+    assert_eq!(histogram.range, -0.5..=3.5);
+    assert_eq!(histogram.bins, vec![1, 2, 3, 1]);
+    assert_eq!(histogram.bin(-10.0), 0);
+    assert_eq!(histogram.bin(10.0), 3);
+    assert_eq!(histogram.bin_range(1), 0.5..=1.5);
+}
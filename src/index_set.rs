@@ -32,6 +32,14 @@ impl IndexSet {
         }
     }
 
+    pub fn union_with(&mut self, other: &IndexSet) {
+        self.indices.union_with(&other.indices);
+    }
+
+    pub fn clear(&mut self) {
+        self.indices.clear();
+    }
+
     pub fn contains(&self, (ixx, ixy): (usize, usize)) -> bool {
         self.indices[ixx + ixy * self.matrix_width]
     }
@@ -0,0 +1,24 @@
+use num_traits::One;
+use serde::{Deserialize, Serialize};
+
+/// Makes the contribution of a neighbour depend on its own type as well as the type of the centre cell
+/// both tables are indexed by `[centre type][neighbour type]`
+#[derive(Serialize, Deserialize, Clone)]
+pub enum Interaction<T, C> {
+    /// the kernel of the centre type is used, every neighbour is multiplied by a weight
+    Weights([[T; 9]; 9]),
+    /// every pair of types has its own kernel
+    Kernels(Box<[[C; 9]; 9]>),
+}
+
+impl<T: Copy + One, C: Copy> Interaction<T, C> {
+    /// all weights are 1, the convolution does not change
+    pub fn neutral_weights() -> Self {
+        Interaction::Weights([[One::one(); 9]; 9])
+    }
+
+    /// every centre type starts with its kernel for all neighbour types
+    pub fn kernels_from(kernels: &[C; 9]) -> Self {
+        Interaction::Kernels(Box::new(kernels.map(|kernel| [kernel; 9])))
+    }
+}
@@ -0,0 +1,66 @@
+use crate::convolution::*;
+use cell_type::CellType;
+use color::Color;
+use matrices::{const_matrix::*, vec_matrix::VecMatrix};
+use rugol::RugolState;
+
+pub mod activity_map;
+pub mod age;
+pub mod app_config;
+pub mod breakpoint;
+pub mod cell_type;
+pub mod color;
+pub mod continuous;
+pub mod convolution;
+pub mod elementary;
+pub mod fade;
+pub mod fire_counts;
+pub mod histogram;
+pub mod index_set;
+pub mod interaction;
+pub mod margolus;
+pub mod mask;
+pub mod reaction_diffusion;
+pub mod reduction;
+pub mod region;
+pub mod render_mini;
+pub mod rugol;
+pub mod rule_lint;
+pub mod rules;
+pub mod save_file;
+pub mod script;
+pub mod second_order;
+pub mod self_check;
+pub mod timeline;
+pub mod topology;
+pub mod traits;
+pub mod update_rule;
+pub mod zoom_window;
+
+const CELLS: [(usize, usize); 5] = [(10, 5), (100, 50), (200, 100), (400, 200), (800, 400)];
+static WARN_TEXT: &str = "Warning: Depending on the settings this program may produce bright flashing and/or pulsating images";
+
+pub enum ConvolutionWidth {
+    Three,
+    Five,
+}
+
+// type RState = RugolState<ConstMatrix<u8, CELLS_X, CELLS_Y>, ConstMatrix<u8, 3, 3>>;
+// width of the convolution matrix
+const CONVOLUTION_WIDTH: usize = 7;
+pub type FieldType = f32;
+// FieldType matrix
+type BaseMatrix<const CW: usize> = Convolution<FieldType, CW>;
+type ConvolutionMatrix<const CW: usize> = ConstMatrix<FieldType, CW, CW>;
+type RState<const CW: usize> =
+    RugolState<BaseMatrix<CW>, ConvolutionMatrix<CW>, VecMatrix<Color>, CW>;
+
+pub enum UiMode {
+    Warn,
+    Main,
+    #[cfg(not(target_arch = "wasm32"))]
+    OpenFile,
+    #[cfg(not(target_arch = "wasm32"))]
+    SaveFile,
+    Help,
+}
@@ -0,0 +1,183 @@
+use crate::{mask::Mask, CellType};
+use matrices::{traits::Matrix, vec_matrix::VecMatrix};
+use serde::{Deserialize, Serialize};
+
+/// cells of a 2×2 block in the order top left, top right, bottom left, bottom right
+pub type Block = [CellType; 4];
+
+/// rotates a block by 90° clockwise
+pub fn rotate_block([tl, tr, bl, br]: Block) -> Block {
+    [bl, tl, br, tr]
+}
+
+/// the block and its rotations by 90°, 180° and 270°
+pub fn block_rotations(block: Block) -> [Block; 4] {
+    let r1 = rotate_block(block);
+    let r2 = rotate_block(r1);
+    [block, r1, r2, rotate_block(r2)]
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BlockRule {
+    pub from: Block,
+    pub to: Block,
+}
+
+impl BlockRule {
+    pub fn new(from: Block, to: Block) -> Self {
+        BlockRule { from, to }
+    }
+
+    pub fn rotated(&self) -> Self {
+        BlockRule::new(rotate_block(self.from), rotate_block(self.to))
+    }
+}
+
+/// lookup table for the blocks, blocks without a matching entry stay unchanged
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BlockRules {
+    pub table: Vec<BlockRule>,
+    /// every entry also matches the rotations of its `from` block,
+    /// the `to` block is rotated the same way
+    pub brotations: bool,
+}
+
+impl BlockRules {
+    /// first matching entry wins
+    pub fn apply(&self, block: Block) -> Block {
+        for rule in self.table.iter() {
+            if self.brotations {
+                let mut rotated = rule.clone();
+                for _ in 0..4 {
+                    if rotated.from == block {
+                        return rotated.to;
+                    }
+                    rotated = rotated.rotated();
+                }
+            } else if rule.from == block {
+                return rule.to;
+            }
+        }
+        block
+    }
+
+    /// replaces every entry by its 4 rotations, entries that are already present are skipped
+    pub fn expand_rotations(&mut self) {
+        let mut table: Vec<BlockRule> = Vec::with_capacity(4 * self.table.len());
+        for rule in self.table.iter() {
+            let mut rotated = rule.clone();
+            for _ in 0..4 {
+                if !table.iter().any(|r| r.from == rotated.from) {
+                    table.push(rotated.clone());
+                }
+                rotated = rotated.rotated();
+            }
+        }
+        self.table = table;
+        self.brotations = false;
+    }
+
+    /// billiard ball machine: single particles move diagonally,
+    /// two particles on a diagonal collide and leave on the other diagonal
+    pub fn billiard_ball() -> Self {
+        use CellType::{NoCell as O, A};
+        BlockRules {
+            table: vec![
+                BlockRule::new([A, O, O, O], [O, O, O, A]),
+                BlockRule::new([A, O, O, A], [O, A, A, O]),
+            ],
+            brotations: true,
+        }
+    }
+
+    /// lattice gas: every block is rotated by 180°, except for head on collisions
+    /// which leave at a right angle
+    pub fn gas() -> Self {
+        use CellType::{NoCell as O, A};
+        BlockRules {
+            table: vec![
+                BlockRule::new([A, O, O, O], [O, O, O, A]),
+                BlockRule::new([A, A, O, O], [O, O, A, A]),
+                BlockRule::new([A, A, A, O], [O, A, A, A]),
+                BlockRule::new([A, O, O, A], [O, A, A, O]),
+            ],
+            brotations: true,
+        }
+    }
+
+    /// falling sand: `CellType::A` falls down and piles up
+    pub fn sand() -> Self {
+        use CellType::{NoCell as O, A};
+        BlockRules {
+            table: vec![
+                BlockRule::new([A, O, O, O], [O, O, A, O]),
+                BlockRule::new([O, A, O, O], [O, O, O, A]),
+                BlockRule::new([A, A, O, O], [O, O, A, A]),
+                BlockRule::new([A, O, O, A], [O, O, A, A]),
+                BlockRule::new([O, A, A, O], [O, O, A, A]),
+                BlockRule::new([A, A, A, O], [A, O, A, A]),
+                BlockRule::new([A, A, O, A], [O, A, A, A]),
+            ],
+            brotations: false,
+        }
+    }
+}
+
+impl Default for BlockRules {
+    fn default() -> Self {
+        Self::billiard_ball()
+    }
+}
+
+/// Block automaton, the board is split into 2×2 blocks which are rewritten as a whole
+#[derive(Default)]
+pub struct Margolus {
+    pub rules: BlockRules,
+    /// the blocks are offset by one cell in both directions in odd generations
+    pub generation: usize,
+}
+
+impl Margolus {
+    /// blocks wrap around the borders of the board
+    /// blocks which contain a masked cell are not changed
+    pub fn step(&mut self, cells: &mut VecMatrix<CellType>, mask: &VecMatrix<Mask>) {
+        let w = cells.width();
+        let h = cells.height();
+        let offset = self.generation % 2;
+        for bixy in 0..h / 2 {
+            for bixx in 0..w / 2 {
+                let (x0, y0) = (2 * bixx + offset, 2 * bixy + offset);
+                let ixs = [
+                    (x0 % w, y0 % h),
+                    ((x0 + 1) % w, y0 % h),
+                    (x0 % w, (y0 + 1) % h),
+                    ((x0 + 1) % w, (y0 + 1) % h),
+                ];
+                if ixs.iter().any(|ix| !mask.index(*ix).is_open()) {
+                    continue;
+                }
+                let block = ixs.map(|ix| cells.index(ix));
+                let next = self.rules.apply(block);
+                if next != block {
+                    for (ix, cell) in ixs.into_iter().zip(next) {
+                        cells.set_at_index(ix, cell);
+                    }
+                }
+            }
+        }
+        self.generation += 1;
+    }
+}
+
+#[test]
+fn billiard_ball_rotations() {
+    use CellType::{NoCell as O, A};
+    let rules = BlockRules::billiard_ball();
+    assert_eq!(rules.apply([O, O, A, O]), [O, A, O, O]);
+    assert_eq!(rules.apply([O, A, A, O]), [A, O, O, A]);
+    assert_eq!(rules.apply([A, A, O, O]), [A, A, O, O]);
+    let mut expanded = rules.clone();
+    expanded.expand_rotations();
+    assert_eq!(expanded.table.len(), 6);
+    assert_eq!(expanded.apply([O, O, A, O]), [O, A, O, O]);
+}
@@ -0,0 +1,59 @@
+use crate::{
+    color::{Color, DARKGRAY, GREEN, RED},
+    CellType, FieldType,
+};
+use serde::{Deserialize, Serialize};
+
+/// Marks cells which are not changed by the simulation
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mask {
+    /// the cell is updated normally
+    #[default]
+    Open,
+    /// the cell keeps the state it had when the wall was placed
+    Wall,
+    /// the cell is always in the given state, continuous fields are always 1
+    Source(CellType),
+    /// the cell is always empty, continuous fields are always 0
+    Sink,
+}
+
+impl Mask {
+    pub fn is_open(&self) -> bool {
+        *self == Mask::Open
+    }
+
+    /// state the cell is kept in, None if the cell keeps its current state
+    pub fn forced_cell(&self) -> Option<CellType> {
+        match self {
+            Mask::Source(cell) => Some(*cell),
+            Mask::Sink => Some(CellType::NoCell),
+            Mask::Open | Mask::Wall => None,
+        }
+    }
+
+    /// value continuous fields are kept at, None if the field keeps its current value
+    pub fn forced_value(&self) -> Option<FieldType> {
+        match self {
+            Mask::Source(_) => Some(1.0),
+            Mask::Sink => Some(0.0),
+            Mask::Open | Mask::Wall => None,
+        }
+    }
+
+    /// blends the overlay of the mask over `color`
+    pub fn overlay(&self, color: Color) -> Color {
+        let overlay = match self {
+            Mask::Open => return color,
+            Mask::Wall => DARKGRAY,
+            Mask::Source(_) => GREEN,
+            Mask::Sink => RED,
+        };
+        Color::new(
+            0.5 * (color.r + overlay.r),
+            0.5 * (color.g + overlay.g),
+            0.5 * (color.b + overlay.b),
+            color.a,
+        )
+    }
+}
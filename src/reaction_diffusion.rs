@@ -0,0 +1,312 @@
+use crate::{
+    color::{Color, BLACK, GREEN, ORANGE, SKYBLUE},
+    convolution::{Convolution, KernelParams},
+    index_set::IndexSet,
+    mask::Mask,
+    reduction::Reduction,
+    topology::Topology,
+    traits::ConvolutionT,
+    BaseMatrix, CellType, ConvolutionMatrix, FieldType,
+};
+use matrices::{traits::Matrix, vec_matrix::VecMatrix};
+use quad_rand::gen_range;
+use serde::{Deserialize, Serialize};
+
+/// `(coefficient + feed_factor * feed + kill_factor * kill) * field_0^powers[0] * field_1^powers[1] ...`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReactionTerm {
+    pub coefficient: FieldType,
+    pub feed_factor: FieldType,
+    pub kill_factor: FieldType,
+    /// exponent for every field
+    pub powers: Vec<u8>,
+}
+
+impl ReactionTerm {
+    pub fn new(coefficient: FieldType, powers: Vec<u8>) -> Self {
+        ReactionTerm {
+            coefficient,
+            feed_factor: 0.0,
+            kill_factor: 0.0,
+            powers,
+        }
+    }
+
+    pub fn feed(mut self, feed_factor: FieldType) -> Self {
+        self.feed_factor = feed_factor;
+        self
+    }
+
+    pub fn kill(mut self, kill_factor: FieldType) -> Self {
+        self.kill_factor = kill_factor;
+        self
+    }
+
+    pub fn eval(&self, values: &[FieldType], feed: FieldType, kill: FieldType) -> FieldType {
+        let mut res = self.coefficient + self.feed_factor * feed + self.kill_factor * kill;
+        for (value, power) in values.iter().zip(self.powers.iter()) {
+            res *= value.powi(*power as i32);
+        }
+        res
+    }
+}
+
+/// One of the coupled fields
+/// it diffuses with the diffusion kernel of the same index
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReactionField {
+    pub name: String,
+    /// factor for the result of the diffusion kernel
+    pub diffusion: FieldType,
+    pub color: Color,
+    /// value of the whole field after seeding
+    pub initial: FieldType,
+    /// value inside the seeded spots and when drawing with the mouse
+    pub seed: FieldType,
+    /// the sum of these is the reaction rate of the field
+    pub reaction: Vec<ReactionTerm>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReactionParams {
+    pub fields: Vec<ReactionField>,
+    pub feed: FieldType,
+    pub kill: FieldType,
+    pub dt: FieldType,
+}
+
+impl ReactionParams {
+    /// u + 2v -> 3v, u is fed and v is killed
+    pub fn gray_scott() -> Self {
+        ReactionParams {
+            fields: vec![
+                ReactionField {
+                    name: "u".to_string(),
+                    diffusion: 1.0,
+                    color: BLACK,
+                    initial: 1.0,
+                    seed: 0.5,
+                    reaction: vec![
+                        ReactionTerm::new(-1.0, vec![1, 2]),
+                        ReactionTerm::new(0.0, vec![0, 0]).feed(1.0),
+                        ReactionTerm::new(0.0, vec![1, 0]).feed(-1.0),
+                    ],
+                },
+                ReactionField {
+                    name: "v".to_string(),
+                    diffusion: 0.5,
+                    color: SKYBLUE,
+                    initial: 0.0,
+                    seed: 0.25,
+                    reaction: vec![
+                        ReactionTerm::new(1.0, vec![1, 2]),
+                        ReactionTerm::new(0.0, vec![0, 1]).feed(-1.0).kill(-1.0),
+                    ],
+                },
+            ],
+            feed: 0.055,
+            kill: 0.062,
+            dt: 1.0,
+        }
+    }
+
+    /// u is the activator, v the inhibitor
+    /// feed is the external stimulus, kill the time scale of v
+    pub fn fitzhugh_nagumo() -> Self {
+        ReactionParams {
+            fields: vec![
+                ReactionField {
+                    name: "u".to_string(),
+                    diffusion: 1.0,
+                    color: ORANGE,
+                    initial: 0.0,
+                    seed: 1.0,
+                    reaction: vec![
+                        ReactionTerm::new(1.0, vec![1, 0]),
+                        ReactionTerm::new(-1.0, vec![3, 0]),
+                        ReactionTerm::new(-1.0, vec![0, 1]),
+                        ReactionTerm::new(0.0, vec![0, 0]).feed(1.0),
+                    ],
+                },
+                ReactionField {
+                    name: "v".to_string(),
+                    diffusion: 0.0,
+                    color: GREEN,
+                    initial: 0.0,
+                    seed: 0.0,
+                    reaction: vec![
+                        ReactionTerm::new(0.0, vec![1, 0]).kill(1.0),
+                        ReactionTerm::new(0.0, vec![0, 0]).kill(0.7),
+                        ReactionTerm::new(0.0, vec![0, 1]).kill(-0.8),
+                    ],
+                },
+            ],
+            feed: 0.0,
+            kill: 0.08,
+            dt: 0.1,
+        }
+    }
+}
+
+/// Two or more continuous fields coupled by reaction terms
+pub struct ReactionDiffusion<const CW: usize> {
+    pub params: ReactionParams,
+    /// the diffusion kernel of every field, separate from the kernels of the other modes
+    pub kernels: [ConvolutionMatrix<CW>; 9],
+    fields: Vec<BaseMatrix<CW>>,
+    /// result of the diffusion kernel for every field
+    diffused: Vec<VecMatrix<FieldType>>,
+    width: usize,
+    height: usize,
+}
+
+impl<const CW: usize> ReactionDiffusion<CW> {
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut kernel = ConvolutionMatrix::new(CW, CW, 0.0);
+        laplacian_kernel(&mut kernel);
+        let mut res = ReactionDiffusion {
+            params: ReactionParams::gray_scott(),
+            kernels: [kernel; 9],
+            fields: Vec::new(),
+            diffused: Vec::new(),
+            width,
+            height,
+        };
+        res.seed();
+        res
+    }
+
+    /// resets the fields if the size of the board or the number of fields changed
+    pub fn resize(&mut self, width: usize, height: usize) {
+        if self.width != width
+            || self.height != height
+            || self.fields.len() != self.params.fields.len()
+        {
+            self.width = width;
+            self.height = height;
+            self.seed();
+        }
+    }
+
+    /// sets every field to its initial value and places random spots with the seed values
+    pub fn seed(&mut self) {
+        let (w, h) = (self.width, self.height);
+        self.fields = self
+            .params
+            .fields
+            .iter()
+            .map(|field| Convolution::new(w, h, field.initial))
+            .collect();
+        self.diffused = self
+            .params
+            .fields
+            .iter()
+            .map(|_| VecMatrix::new(w, h, 0.0))
+            .collect();
+        let spot_size = (w.min(h) / 10).max(1);
+        for _ in 0..((w * h) / 2000).max(1) {
+            let x = gen_range(0, w);
+            let y = gen_range(0, h);
+            for ixx in x..(x + spot_size).min(w) {
+                for ixy in y..(y + spot_size).min(h) {
+                    self.set_seed_at_index((ixx, ixy));
+                }
+            }
+        }
+    }
+
+    pub fn set_seed_at_index(&mut self, ix: (usize, usize)) {
+        for (field, params) in self.fields.iter_mut().zip(self.params.fields.iter()) {
+            field.set_at_index(ix, params.seed);
+        }
+    }
+
+    pub fn step(
+        &mut self,
+        cells: &VecMatrix<CellType>,
+        mask: &VecMatrix<Mask>,
+        topology: Topology,
+    ) {
+        let mut indices = IndexSet::new(self.width, self.height);
+        indices.insert_rect(&((0, self.width - 1)..=(0, self.height - 1)));
+        for (ix, (field, diffused)) in self.fields.iter().zip(self.diffused.iter_mut()).enumerate()
+        {
+            let params = KernelParams {
+                kernels: &self.kernels[ix..=ix],
+                bsingle_kernel: true,
+                interaction: None,
+                // diffusion is always a weighted sum
+                reductions: &[Reduction::Sum],
+                topology,
+                // the border keeps the initial value of the field
+                bfixed_border: true,
+            };
+            field.convolution(&params, cells, diffused, &indices);
+        }
+        let (feed, kill, dt) = (self.params.feed, self.params.kill, self.params.dt);
+        let mut values = vec![0.0; self.fields.len()];
+        for (ixx, ixy) in indices.iter() {
+            let cell_mask = mask.index((ixx, ixy));
+            if !cell_mask.is_open() {
+                // sources are kept at the seed, sinks at the initial value, walls are unchanged
+                for (field, params) in self.fields.iter_mut().zip(self.params.fields.iter()) {
+                    match cell_mask {
+                        Mask::Source(_) => field.set_at_index((ixx, ixy), params.seed),
+                        Mask::Sink => field.set_at_index((ixx, ixy), params.initial),
+                        Mask::Open | Mask::Wall => {}
+                    }
+                }
+                continue;
+            }
+            for (value, field) in values.iter_mut().zip(self.fields.iter()) {
+                *value = field.index((ixx, ixy));
+            }
+            for (ix, params) in self.params.fields.iter().enumerate() {
+                let reaction: FieldType = params
+                    .reaction
+                    .iter()
+                    .map(|term| term.eval(&values, feed, kill))
+                    .sum();
+                let diffusion = params.diffusion * self.diffused[ix].index((ixx, ixy));
+                self.fields[ix].set_at_index((ixx, ixy), values[ix] + dt * (diffusion + reaction));
+            }
+        }
+    }
+
+    /// sum of the field colors weighted by the field values
+    pub fn color_at(&self, ixx: usize, ixy: usize) -> Color {
+        let mut res = [0.0, 0.0, 0.0, 1.0];
+        for (field, params) in self.fields.iter().zip(self.params.fields.iter()) {
+            let value = field.index((ixx, ixy)).clamp(0.0, 1.0);
+            let color: [f32; 4] = params.color.into();
+            for c in 0..3 {
+                res[c] = (res[c] + color[c] * value).min(1.0);
+            }
+        }
+        res.into()
+    }
+}
+
+/// diffusion kernel with a sum of 0, the center is -1
+pub fn laplacian_kernel<C: Matrix<FieldType>>(kernel: &mut C) {
+    let w = kernel.width();
+    let h = kernel.height();
+    kernel.clear(0.0);
+    let (cx, cy) = (w / 2, h / 2);
+    kernel.set_at_index((cx, cy), -1.0);
+    for (dx, dy, val) in [
+        (-1, 0, 0.2),
+        (1, 0, 0.2),
+        (0, -1, 0.2),
+        (0, 1, 0.2),
+        (-1, -1, 0.05),
+        (1, -1, 0.05),
+        (-1, 1, 0.05),
+        (1, 1, 0.05),
+    ] {
+        kernel.set_at_index(
+            ((cx as isize + dx) as usize, (cy as isize + dy) as usize),
+            val,
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use num_traits::{One, Zero};
+use serde::{Deserialize, Serialize};
+use std::ops::Div;
+
+/// How the contributions `field * kernel` of the neighbours are combined into the accumulated value
+/// except for `Sum` only the entries with a non zero kernel value contribute
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Reduction {
+    #[default]
+    Sum,
+    Max,
+    Min,
+    Product,
+    /// number of non zero contributions
+    CountNonZero,
+    /// sum of the contributions divided by the sum of the kernel values
+    WeightedMean,
+    Median,
+}
+
+impl Reduction {
+    pub const ALL: [Reduction; 7] = [
+        Reduction::Sum,
+        Reduction::Max,
+        Reduction::Min,
+        Reduction::Product,
+        Reduction::CountNonZero,
+        Reduction::WeightedMean,
+        Reduction::Median,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Reduction::Sum => "sum",
+            Reduction::Max => "max",
+            Reduction::Min => "min",
+            Reduction::Product => "product",
+            Reduction::CountNonZero => "count non zero",
+            Reduction::WeightedMean => "weighted mean",
+            Reduction::Median => "median",
+        }
+    }
+}
+
+/// Running state of a reduction over the neighbours of one cell
+pub struct Reducer<T> {
+    reduction: Reduction,
+    acc: T,
+    weight_sum: T,
+    count: usize,
+    /// only used by `Reduction::Median`
+    values: Vec<T>,
+}
+
+impl<T: Copy + Zero + One + PartialOrd + Div<Output = T>> Reducer<T> {
+    pub fn new(reduction: Reduction) -> Self {
+        Reducer {
+            reduction,
+            acc: Zero::zero(),
+            weight_sum: Zero::zero(),
+            count: 0,
+            values: Vec::new(),
+        }
+    }
+
+    /// one reducer for every reduction, they can be reused for all cells of a step
+    pub fn all(reductions: &[Reduction]) -> Vec<Self> {
+        reductions
+            .iter()
+            .map(|reduction| Self::new(*reduction))
+            .collect()
+    }
+
+    pub fn add(&mut self, value: T, weight: T) {
+        let contribution = value * weight;
+        if self.reduction == Reduction::Sum {
+            self.acc = self.acc + contribution;
+            return;
+        }
+        if weight.is_zero() {
+            return;
+        }
+        match self.reduction {
+            Reduction::Sum => {}
+            Reduction::Max => {
+                if self.count == 0 || contribution > self.acc {
+                    self.acc = contribution;
+                }
+            }
+            Reduction::Min => {
+                if self.count == 0 || contribution < self.acc {
+                    self.acc = contribution;
+                }
+            }
+            Reduction::Product => {
+                self.acc = if self.count == 0 {
+                    contribution
+                } else {
+                    self.acc * contribution
+                };
+            }
+            Reduction::CountNonZero => {
+                if !contribution.is_zero() {
+                    self.acc = self.acc + One::one();
+                }
+            }
+            Reduction::WeightedMean => {
+                self.acc = self.acc + contribution;
+                self.weight_sum = self.weight_sum + weight;
+            }
+            Reduction::Median => self.values.push(contribution),
+        }
+        self.count += 1;
+    }
+
+    /// 0 if no neighbour contributed, the reducer starts over for the next cell
+    pub fn finish(&mut self) -> T {
+        let res = self.result();
+        self.acc = Zero::zero();
+        self.weight_sum = Zero::zero();
+        self.count = 0;
+        self.values.clear();
+        res
+    }
+
+    fn result(&mut self) -> T {
+        match self.reduction {
+            Reduction::WeightedMean if self.weight_sum.is_zero() => Zero::zero(),
+            Reduction::WeightedMean => self.acc / self.weight_sum,
+            Reduction::Median if self.values.is_empty() => Zero::zero(),
+            Reduction::Median => {
+                self.values
+                    .sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                let mid = self.values.len() / 2;
+                if self.values.len() % 2 == 1 {
+                    self.values[mid]
+                } else {
+                    (self.values[mid - 1] + self.values[mid]) / (T::one() + T::one())
+                }
+            }
+            _ => self.acc,
+        }
+    }
+}
+
+#[test]
+fn reductions_of_neighbours() {
+    let neighbours = [(1.0, 1.0), (3.0, 1.0), (0.0, 1.0), (5.0, 0.0), (2.0, 0.5)];
+    let reduce = |reduction| {
+        let mut reducer = Reducer::<f32>::new(reduction);
+        for (value, weight) in neighbours {
+            reducer.add(value, weight);
+        }
+        reducer.finish()
+    };
+    assert_eq!(reduce(Reduction::Sum), 5.0);
+    assert_eq!(reduce(Reduction::Max), 3.0);
+    assert_eq!(reduce(Reduction::Min), 0.0);
+    assert_eq!(reduce(Reduction::Product), 0.0);
+    assert_eq!(reduce(Reduction::CountNonZero), 3.0);
+    assert_eq!(reduce(Reduction::WeightedMean), 5.0 / 3.5);
+    assert_eq!(reduce(Reduction::Median), 1.0);
+    let mut reducer = Reducer::<f32>::new(Reduction::Max);
+    reducer.add(3.0, 1.0);
+    assert_eq!(reducer.finish(), 3.0);
+    reducer.add(-2.0, 1.0);
+    assert_eq!(reducer.finish(), -2.0);
+}
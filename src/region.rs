@@ -0,0 +1,30 @@
+use crate::{
+    color::{Color, GOLD, LIME, ORANGE, PINK, SKYBLUE, VIOLET},
+    rules::RuleSet,
+    FieldType,
+};
+use serde::{Deserialize, Serialize};
+
+/// Part of the board with its own rules and optionally its own kernels
+/// region 0 is the rest of the board, it uses the rules and kernels of `RugolState`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Region<C> {
+    pub rules: RuleSet<FieldType>,
+    /// the kernels of region 0 are used if this is None
+    pub kernels: Option<[C; 9]>,
+}
+
+/// blends the overlay color of region `region` over `color`, region 0 has no overlay
+pub fn region_overlay(region: u8, color: Color) -> Color {
+    const COLORS: [Color; 6] = [SKYBLUE, ORANGE, LIME, PINK, VIOLET, GOLD];
+    if region == 0 {
+        return color;
+    }
+    let overlay = COLORS[(region as usize - 1) % COLORS.len()];
+    Color::new(
+        0.7 * color.r + 0.3 * overlay.r,
+        0.7 * color.g + 0.3 * overlay.g,
+        0.7 * color.b + 0.3 * overlay.b,
+        color.a,
+    )
+}
@@ -5,7 +5,7 @@ use matrices::traits::Matrix;
 use miniquad::*;
 use num_traits::Zero;
 
-use crate::{zoom_window::ZoomWindow, RState, CONVOLUTION_WIDTH};
+use crate::{update_rule::RuleRegistry, zoom_window::ZoomWindow, RState, CONVOLUTION_WIDTH};
 
 #[derive(Clone, Copy, Debug)]
 #[repr(C)]
@@ -26,6 +26,8 @@ struct Stage {
     pipeline: Pipeline,
     bindings: Vec<Bindings>,
     vertices: Vec<Vec<Vertex>>,
+    /// number of vertices of a single cell
+    corners: usize,
     vertex_buffers: Vec<Buffer>,
     index_buffer: Buffer,
     gol: RState<CONVOLUTION_WIDTH>,
@@ -35,7 +37,7 @@ struct Stage {
 }
 
 impl Stage {
-    pub fn new(ctx: &mut Context) -> Stage {
+    pub fn new(ctx: &mut Context, rule_registry: RuleRegistry) -> Stage {
         let index_buffer = Buffer::immutable::<u16>(ctx, BufferType::IndexBuffer, &[]);
         let bindings = vec![];
 
@@ -51,6 +53,7 @@ impl Stage {
             shader,
         );
         let mut gol = <RState<CONVOLUTION_WIDTH>>::new();
+        gol.rule_registry = rule_registry;
         gol.donut_all_kernels(0..=1, Zero::zero());
 
         // arrows
@@ -90,6 +93,7 @@ impl Stage {
             pipeline,
             bindings,
             vertices: vec![],
+            corners: 4,
             vertex_buffers: vec![],
             index_buffer,
             gol,
@@ -108,62 +112,32 @@ impl Stage {
         self.bindings.clear();
         let width = self.gol.get_fields().width();
         let height = self.gol.get_fields().height();
-        // set up indices
+        let topology = self.gol.config.topology;
+        self.corners = topology.corners();
+        // set up indices, every cell is drawn as a triangle fan
         let mut indices: Vec<u16> = Vec::new();
         for index in 0..width {
-            let index_base = 4 * index as u16;
-            indices.extend_from_slice(&[
-                index_base,
-                index_base + 1,
-                index_base + 2,
-                index_base,
-                index_base + 2,
-                index_base + 3,
-            ]);
+            let index_base = (self.corners * index) as u16;
+            for corner in 1..(self.corners as u16 - 1) {
+                indices.extend_from_slice(&[
+                    index_base,
+                    index_base + corner,
+                    index_base + corner + 1,
+                ]);
+            }
         }
         self.index_buffer = Buffer::immutable(ctx, BufferType::IndexBuffer, &indices);
         for ixy in 0..height {
             self.vertices.push(vec![]);
             for ixx in 0..width {
-                let matrix_width = self.gol.get_fields().width() as f32;
-                let matrix_height = self.gol.get_fields().height() as f32;
-                let x = (ixx as f32 * 2.0) / matrix_width;
-                let y = (ixy as f32 * 2.0) / matrix_height;
-                let w = 2.0 / (width as f32);
-                let h = 2.0 / (height as f32);
-                let f = self.gol.config.cell_size_factor;
-                let x_adjust = 0.5 * w * (1.0 - f) - 1.0;
-                let y_adjust = 0.5 * h * (1.0 - f) - 1.0;
-                let x = x + x_adjust;
-                let y = y + y_adjust;
-                let w = w * f;
-                let h = h * f;
-
-                let color: [f32; 4] = if self.gol.config.bfade {
-                    self.gol.fader.index(ixx, ixy).into()
-                } else {
-                    self.gol.cell_type_map[self.gol.get_cells().index((ixx, ixy))]
-                        .0
-                        .into()
-                };
-                self.vertices[ixy].extend_from_slice(&[
-                    Vertex {
-                        pos: Vec2 { x, y },
-                        color,
-                    },
-                    Vertex {
-                        pos: Vec2 { x, y: y + h },
-                        color,
-                    },
-                    Vertex {
-                        pos: Vec2 { x: x + w, y: y + h },
-                        color,
-                    },
-                    Vertex {
-                        pos: Vec2 { x: x + w, y },
-                        color,
-                    },
-                ]);
+                let color: [f32; 4] = self.gol.color_at(ixx, ixy).into();
+                for pos in topology.cell_polygon(
+                    (ixx, ixy),
+                    (width, height),
+                    self.gol.config.cell_size_factor,
+                ) {
+                    self.vertices[ixy].push(Vertex { pos, color });
+                }
             }
             //self.vertex_buffer = Buffer::immutable(ctx, BufferType::VertexBuffer, &self.vertices);
             self.vertex_buffers.push(Buffer::stream(
@@ -186,9 +160,13 @@ impl Stage {
             self.gol.get_fields().height(),
         );
         let (mouse_x, mouse_y) = self.zoom_window.mouse_to_screen(ctx, mouse_x, mouse_y);
-        let ixx = (field_width as f32 * mouse_x / win_width) as usize;
-        let ixy = (field_height as f32 * mouse_y / win_height) as usize;
-        (ixx.min(field_width - 1), ixy.min(field_height - 1))
+        self.gol.config.topology.point_to_index(
+            (
+                2.0 * mouse_x / win_width - 1.0,
+                2.0 * mouse_y / win_height - 1.0,
+            ),
+            (field_width, field_height),
+        )
     }
 
     fn draw_circle_at_index(&mut self, ixx: usize, ixy: usize) {
@@ -214,23 +192,20 @@ impl EventHandler for Stage {
             self.new_size_selected(ctx);
             self.gol.config.bnew_size = false;
         }
-        if !self.gol.config.paused {
+        if !self.gol.script.queue.is_empty() {
+            self.gol.run_script_commands();
+        } else if !self.gol.config.paused {
             self.gol.step();
         }
         if self.gol.config.bupdate {
             for ixy in 0..self.gol.get_fields().height() {
                 for ixx in 0..self.gol.get_fields().width() {
-                    let color: [f32; 4] = if self.gol.config.bfade {
-                        self.gol.fader.index(ixx, ixy).into()
-                    } else {
-                        self.gol.cell_type_map[self.gol.get_cells().index((ixx, ixy))]
-                            .0
-                            .into()
-                    };
-                    self.vertices[ixy][ixx * 4].color = color;
-                    self.vertices[ixy][ixx * 4 + 1].color = color;
-                    self.vertices[ixy][ixx * 4 + 2].color = color;
-                    self.vertices[ixy][ixx * 4 + 3].color = color;
+                    let color: [f32; 4] = self.gol.color_at(ixx, ixy).into();
+                    for vertex in
+                        &mut self.vertices[ixy][ixx * self.corners..(ixx + 1) * self.corners]
+                    {
+                        vertex.color = color;
+                    }
                 }
 
                 self.vertex_buffers[ixy].update(ctx, &self.vertices[ixy]);
@@ -286,6 +261,14 @@ impl EventHandler for Stage {
         }
         let (ixx, ixy) = self.mouse_pos_to_index(ctx, x, y);
         self.gol.hover_ix = Some((ixx, ixy));
+        if self.gol.config.binspector
+            && !self.gol.config.ui_contains_pointer
+            && self.gol.config.inspect_ix != Some((ixx, ixy))
+        {
+            // the footprint of the kernel moves with the mouse
+            self.gol.config.inspect_ix = Some((ixx, ixy));
+            self.gol.config.bupdate = true;
+        }
         if self.bdraw {
             self.draw_circle_at_index(ixx, ixy);
             match self.last_draw_index {
@@ -436,7 +419,11 @@ impl EventHandler for Stage {
         for binding in &self.bindings {
             ctx.apply_bindings(binding);
 
-            ctx.draw(0, 6 * self.gol.get_fields().width() as i32, 1);
+            ctx.draw(
+                0,
+                (3 * (self.corners - 2) * self.gol.get_fields().width()) as i32,
+                1,
+            );
         }
         ctx.end_render_pass();
         self.egui_mini.run(ctx, |_mq_ctx, egui_ctx| {
@@ -457,7 +444,14 @@ impl EventHandler for Stage {
 }
 
 pub fn mini_main() {
-    miniquad::start(conf::Conf::default(), |ctx| Box::new(Stage::new(ctx)));
+    mini_main_with_rules(RuleRegistry::with_builtins());
+}
+
+/// starts rugol with the `UpdateRule`s of `rule_registry` selectable in the UI
+pub fn mini_main_with_rules(rule_registry: RuleRegistry) {
+    miniquad::start(conf::Conf::default(), |ctx| {
+        Box::new(Stage::new(ctx, rule_registry))
+    });
 }
 
 mod shader {
@@ -0,0 +1,99 @@
+use crate::{
+    cell_type::{CellType, CellTypeMap},
+    histogram::Histogram,
+    rules::RuleSet,
+    FieldType,
+};
+use egui::{Color32, CursorIcon, Pos2, Rect, Sense, Stroke, Ui, Vec2};
+
+const HEIGHT: f32 = 160.0;
+const EDGE_WIDTH: f32 = 8.0;
+
+/// Draws the histogram of the accumulated values of the cells of type `state`
+/// and the ranges of the rules for `state` over it, each range is coloured like its transition
+/// the edges of the ranges can be dragged, an edge can not be dragged past the other edge
+/// returns if a range changed and if an edge is being dragged
+pub fn acc_histogram_ui(
+    ui: &mut Ui,
+    histogram: &Histogram,
+    rules: &mut RuleSet<FieldType>,
+    state: CellType,
+    cell_type_map: &CellTypeMap,
+) -> (bool, bool) {
+    let size = Vec2::new(ui.available_width().max(300.0), HEIGHT);
+    let (response, painter) = ui.allocate_painter(size, Sense::hover());
+    let rect = response.rect;
+    let to_x = |value: FieldType| {
+        let t = (value - histogram.range.start()) / histogram.span();
+        rect.left() + t.clamp(0.0, 1.0) * rect.width()
+    };
+    let max_count = histogram.bins.iter().copied().max().unwrap_or(0).max(1);
+    let text_color = ui.visuals().text_color();
+    for (bin, count) in histogram.bins.iter().enumerate() {
+        let range = histogram.bin_range(bin);
+        let height = rect.height() * *count as f32 / max_count as f32;
+        painter.rect_filled(
+            Rect::from_min_max(
+                Pos2::new(to_x(*range.start()), rect.bottom() - height),
+                Pos2::new(to_x(*range.end()), rect.bottom()),
+            ),
+            0.0,
+            text_color.linear_multiply(0.6),
+        );
+    }
+    let mut changed = false;
+    let mut bdragging = false;
+    for (ix, rule) in rules
+        .rules
+        .iter_mut()
+        .enumerate()
+        .filter(|(_, rule)| rule.state == state)
+    {
+        let [r, g, b, _]: [u8; 4] = cell_type_map[rule.transition].0.into();
+        let color = Color32::from_rgb(r, g, b);
+        let (start, end) = (to_x(*rule.range.start()), to_x(*rule.range.end()));
+        painter.rect_filled(
+            Rect::from_min_max(Pos2::new(start, rect.top()), Pos2::new(end, rect.bottom())),
+            0.0,
+            color.linear_multiply(0.25),
+        );
+        let mut edges = [*rule.range.start(), *rule.range.end()];
+        let mut bdragged = false;
+        for edge in 0..2 {
+            let value = edges[edge];
+            let x = to_x(value);
+            painter.line_segment(
+                [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+                Stroke::new(2.0, color),
+            );
+            let edge_rect = Rect::from_center_size(
+                Pos2::new(x, rect.center().y),
+                Vec2::new(EDGE_WIDTH, HEIGHT),
+            );
+            let edge_response = ui
+                .interact(edge_rect, response.id.with((ix, edge)), Sense::drag())
+                .on_hover_cursor(CursorIcon::ResizeHorizontal)
+                .on_hover_text(format!("rule {ix}: {value}"));
+            if edge_response.dragged() {
+                let value = value + edge_response.drag_delta().x * histogram.span() / rect.width();
+                edges[edge] = if edge == 0 {
+                    value.min(edges[1])
+                } else {
+                    value.max(edges[0])
+                };
+                bdragged = true;
+            }
+        }
+        bdragging |= bdragged;
+        if bdragged {
+            changed = true;
+            rule.range = edges[0]..=edges[1];
+        }
+    }
+    ui.horizontal(|ui| {
+        ui.label(format!("{:.2}", histogram.range.start()));
+        ui.add_space((ui.available_width() - 40.0).max(0.0));
+        ui.label(format!("{:.2}", histogram.range.end()));
+    });
+    (changed, bdragging)
+}
@@ -1,5 +1,11 @@
 use crate::{
-    app_config::AppConfig, cell_type::CellTypeMap, color::WHITE, ConvolutionMatrix, FieldType,
+    app_config::AppConfig,
+    cell_type::{CellType, CellTypeMap},
+    color::WHITE,
+    interaction::Interaction,
+    reduction::Reduction,
+    topology::Topology,
+    ConvolutionMatrix, FieldType,
 };
 use egui::{
     emath::Numeric, Color32, DragValue, ImageButton, Label, Rgba, RichText, Sense, TextureHandle,
@@ -11,6 +17,7 @@ use std::ops::RangeInclusive;
 
 pub struct ConvWrapper<'a, const CW: usize> {
     pub inner: &'a mut [ConvolutionMatrix<CW>; 9],
+    pub interaction: &'a mut Option<Interaction<FieldType, ConvolutionMatrix<CW>>>,
     pub config: &'a mut AppConfig,
     pub cell_type_map: &'a CellTypeMap,
     pub ui_down_arrow: &'a Option<TextureHandle>,
@@ -35,6 +42,13 @@ impl<'a, const CW: usize> TabViewer for ConvWrapper<'a, CW> {
         self.copy_kernels_ui(ui, convolution_index);
         ui.separator();
         self.normalize_kernel_ui(ui, convolution_index);
+        self.reduction_ui(ui, convolution_index);
+        ui.separator();
+        self.neighbourhood_ui(ui, convolution_index);
+        if self.interaction.is_some() {
+            ui.separator();
+            self.interaction_ui(ui, convolution_index);
+        }
     }
 
     fn title(&mut self, tab: &mut Self::Tab) -> WidgetText {
@@ -55,30 +69,69 @@ impl<'a, const CW: usize> ConvWrapper<'a, CW> {
         start..=end
     }
 
+    /// lays out the kernel entries in the shape of the neighbourhood of `topology`
+    /// hexagonal rows are shifted by half a cell, triangular entries are marked
+    /// with their orientation relative to the center cell
+    fn kernel_layout_ui(
+        ui: &mut Ui,
+        topology: Topology,
+        bmirror: bool,
+        (w, h): (usize, usize),
+        mut edit: impl FnMut(&mut Ui, usize, usize),
+    ) {
+        let xs: Vec<usize> = if bmirror {
+            (0..w).rev().collect()
+        } else {
+            (0..w).collect()
+        };
+        let ys: Vec<usize> = if bmirror {
+            (0..h).rev().collect()
+        } else {
+            (0..h).collect()
+        };
+        match topology {
+            Topology::Square => {
+                ui.horizontal(|ui| {
+                    for &x in xs.iter() {
+                        ui.vertical(|ui| {
+                            for &y in ys.iter() {
+                                edit(ui, x, y);
+                            }
+                        });
+                    }
+                });
+            }
+            Topology::Hexagonal | Topology::Triangular => {
+                let indent = 0.5 * ui.spacing().interact_size.x;
+                ui.vertical(|ui| {
+                    for (row, &y) in ys.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if topology == Topology::Hexagonal {
+                                ui.add_space(row as f32 * indent);
+                            }
+                            for &x in xs.iter() {
+                                if topology == Topology::Triangular {
+                                    ui.label(if (x + y) % 2 == 0 { "▲" } else { "▼" });
+                                }
+                                edit(ui, x, y);
+                            }
+                        });
+                    }
+                });
+            }
+        }
+    }
+
     fn colored_matrix_ui(&mut self, ui: &mut Ui, matrix_index: usize) {
         let w = self.inner[matrix_index].width();
         let h = self.inner[matrix_index].height();
-        if self.config.bmirror_conv_kernels {
-            ui.horizontal(|ui| {
-                for x in (0..w).rev() {
-                    ui.vertical(|ui| {
-                        for y in (0..h).rev() {
-                            self.edit_colored_value(ui, matrix_index, x, y);
-                        }
-                    });
-                }
-            });
-        } else {
-            ui.horizontal(|ui| {
-                for x in 0..w {
-                    ui.vertical(|ui| {
-                        for y in 0..h {
-                            self.edit_colored_value(ui, matrix_index, x, y);
-                        }
-                    });
-                }
-            });
-        }
+        Self::kernel_layout_ui(
+            ui,
+            self.config.topology,
+            self.config.bmirror_conv_kernels,
+            (w, h),
+            |ui, x, y| self.edit_colored_value(ui, matrix_index, x, y),
+        );
     }
 
     fn edit_colored_value(&mut self, ui: &mut Ui, matrix_index: usize, x: usize, y: usize) {
@@ -121,27 +174,13 @@ impl<'a, const CW: usize> ConvWrapper<'a, CW> {
 
     fn drag_value_matrix_ui(&mut self, ui: &mut Ui, convolution_index: usize) {
         let conv_matrix = &mut self.inner[convolution_index];
-        if self.config.bmirror_conv_kernels {
-            ui.horizontal(|ui| {
-                for x in (0..conv_matrix.width()).rev() {
-                    ui.vertical(|ui| {
-                        for y in (0..conv_matrix.height()).rev() {
-                            Self::edit_drag_value(conv_matrix, x, y, ui);
-                        }
-                    });
-                }
-            });
-        } else {
-            ui.horizontal(|ui| {
-                for x in 0..conv_matrix.width() {
-                    ui.vertical(|ui| {
-                        for y in 0..conv_matrix.height() {
-                            Self::edit_drag_value(conv_matrix, x, y, ui);
-                        }
-                    });
-                }
-            });
-        }
+        Self::kernel_layout_ui(
+            ui,
+            self.config.topology,
+            self.config.bmirror_conv_kernels,
+            (conv_matrix.width(), conv_matrix.height()),
+            |ui, x, y| Self::edit_drag_value(conv_matrix, x, y, ui),
+        );
     }
 
     fn edit_drag_value(conv_matrix: &mut ConvolutionMatrix<CW>, x: usize, y: usize, ui: &mut Ui) {
@@ -229,4 +268,73 @@ impl<'a, const CW: usize> ConvWrapper<'a, CW> {
             ui.add(DragValue::new(&mut self.config.conv_norms[convolution_index]).speed(0.01));
         });
     }
+
+    /// weights or kernels of the neighbour types for the centre type `convolution_index`
+    fn interaction_ui(&mut self, ui: &mut Ui, convolution_index: usize) {
+        match self.interaction {
+            Some(Interaction::Weights(weights)) => {
+                ui.label("weight of the neighbours of type:");
+                ui.horizontal_wrapped(|ui| {
+                    for (neighbour, weight) in weights[convolution_index].iter_mut().enumerate() {
+                        let neighbour: CellType = neighbour.try_into().unwrap();
+                        ui.label(format!("{neighbour}:"));
+                        ui.add(DragValue::new(weight).speed(0.01));
+                    }
+                });
+            }
+            Some(Interaction::Kernels(kernels)) => {
+                ui.horizontal(|ui| {
+                    ui.label("kernel for the neighbours of type:");
+                    for neighbour in 0..9 {
+                        let cell: CellType = neighbour.try_into().unwrap();
+                        ui.radio_value(
+                            &mut self.config.interaction_neighbour,
+                            neighbour,
+                            cell.to_string(),
+                        );
+                    }
+                });
+                let kernel = &mut kernels[convolution_index][self.config.interaction_neighbour];
+                Self::kernel_layout_ui(
+                    ui,
+                    self.config.topology,
+                    self.config.bmirror_conv_kernels,
+                    (kernel.width(), kernel.height()),
+                    |ui, x, y| Self::edit_drag_value(kernel, x, y, ui),
+                );
+            }
+            None => {}
+        }
+    }
+
+    fn reduction_ui(&mut self, ui: &mut Ui, convolution_index: usize) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("reduction:");
+            for reduction in Reduction::ALL {
+                ui.radio_value(
+                    &mut self.config.reductions[convolution_index],
+                    reduction,
+                    reduction.name(),
+                );
+            }
+        });
+    }
+
+    fn neighbourhood_ui(&mut self, ui: &mut Ui, convolution_index: usize) {
+        let topology = self.config.topology;
+        ui.horizontal(|ui| {
+            ui.label("neighbourhood:");
+            let presets: Vec<(String, usize)> = match topology {
+                Topology::Triangular => vec![("edges".to_string(), 1), ("corners".to_string(), 2)],
+                Topology::Square | Topology::Hexagonal => (1..=CW / 2)
+                    .map(|radius| (format!("radius {radius}"), radius))
+                    .collect(),
+            };
+            for (name, radius) in presets {
+                if ui.button(name).clicked() {
+                    topology.neighbourhood_kernel(&mut self.inner[convolution_index], radius);
+                }
+            }
+        });
+    }
 }
@@ -0,0 +1,269 @@
+use crate::{
+    app_config::SimMode,
+    cell_type::CellType,
+    color::{Color, WHITE},
+    convolution::KernelParams,
+    reduction::Reducer,
+    update_rule::NeighbourhoodCtx,
+    ConvolutionMatrix, FieldType, RState,
+};
+use egui::{Color32, Context, Grid, RichText, Ui, Window};
+use matrices::traits::Matrix;
+use rhai::Scope;
+
+impl<const CW: usize> RState<CW> {
+    /// the kernel window of the inspected cell and the cell itself are drawn lighter
+    pub(super) fn footprint_overlay(&self, (ixx, ixy): (usize, usize), color: Color) -> Color {
+        let (cx, cy) = match self.config.inspect_ix {
+            Some(ix) if self.config.binspector => ix,
+            _ => return color,
+        };
+        let (kixx, kixy) = match (
+            (ixx + CW / 2).checked_sub(cx),
+            (ixy + CW / 2).checked_sub(cy),
+        ) {
+            (Some(kixx), Some(kixy)) if kixx < CW && kixy < CW => (kixx, kixy),
+            _ => return color,
+        };
+        let factor = if (ixx, ixy) == (cx, cy) {
+            0.6
+        } else if self.footprint_weight((cx, cy), (kixx, kixy)) != 0.0 {
+            0.3
+        } else {
+            return color;
+        };
+        Color::new(
+            (1.0 - factor) * color.r + factor * WHITE.r,
+            (1.0 - factor) * color.g + factor * WHITE.g,
+            (1.0 - factor) * color.b + factor * WHITE.b,
+            color.a,
+        )
+    }
+
+    /// weight of the neighbour at the position `(kixx, kixy)` of the kernel window of the cell at `centre`,
+    /// the same weight the convolution uses
+    fn footprint_weight(&self, centre: (usize, usize), kix: (usize, usize)) -> FieldType {
+        let cells = &self.cell_type_vec[self.vec_ix];
+        if centre.0 >= cells.width() || centre.1 >= cells.height() {
+            return 0.0;
+        }
+        let region = self.region_maps[self.vec_ix].index(centre);
+        let params = self.kernel_params(region);
+        self.fields_vec[self.vec_ix]
+            .weight_at(&params, cells, centre, kix)
+            .unwrap_or(0.0)
+    }
+
+    fn kernel_params(&self, region: u8) -> KernelParams<'_, FieldType, ConvolutionMatrix<CW>> {
+        KernelParams {
+            kernels: Self::region_kernels(&self.conv_kernels, &self.regions, region),
+            bsingle_kernel: self.config.bsingle_kernel,
+            interaction: self.interaction.as_ref(),
+            reductions: &self.config.reductions,
+            topology: self.config.topology,
+            bfixed_border: false,
+        }
+    }
+
+    /// shows how the next state of the inspected cell comes about
+    pub(super) fn inspector_window(&mut self, ctx: &Context) {
+        let mut bopen = self.config.binspector;
+        Window::new("inspector")
+            .open(&mut bopen)
+            .show(ctx, |ui| match self.config.inspect_ix {
+                Some(ix) if self.is_valid_index(ix.0, ix.1) => self.inspector_ui(ui, ix),
+                _ => {
+                    ui.label("move the mouse over the board to inspect a cell");
+                }
+            });
+        if bopen != self.config.binspector {
+            self.config.binspector = bopen;
+            self.config.bupdate = true;
+        }
+    }
+
+    fn inspector_ui(&self, ui: &mut Ui, ix: (usize, usize)) {
+        let cells = &self.cell_type_vec[self.vec_ix];
+        let state = cells.index(ix);
+        let region = self.region_maps[self.vec_ix].index(ix);
+        let age = self
+            .config
+            .btrack_age
+            .then(|| self.age.age(ix, self.generation));
+        let mut header = format!("cell {ix:?}: {state}, region {region}");
+        if let Some(age) = age {
+            header += &format!(", age {age}");
+        }
+        ui.label(header);
+        let params = self.kernel_params(region);
+        let fields = &self.fields_vec[self.vec_ix];
+        let contributions = fields.contributions_at(&params, cells, ix);
+        ui.label("neighbours, value × weight:");
+        Grid::new("contributions").striped(true).show(ui, |ui| {
+            for kixy in 0..CW {
+                for kixx in 0..CW {
+                    let text = match contributions
+                        .iter()
+                        .find(|(kix, _, weight)| *kix == (kixx, kixy) && *weight != 0.0)
+                    {
+                        Some((_, value, weight)) => format!("{value}×{weight}"),
+                        None => "·".to_string(),
+                    };
+                    let text = RichText::new(text).monospace();
+                    if (kixx, kixy) == (CW / 2, CW / 2) {
+                        ui.label(text.strong());
+                    } else {
+                        ui.label(text);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+        let kernel_ix = if self.config.bsingle_kernel {
+            0
+        } else {
+            state.as_index()
+        };
+        let acc = fields.convolution_at(&params, cells, ix, &mut Reducer::all(params.reductions));
+        ui.label(format!(
+            "accumulated value ({}): {acc}",
+            self.config.reductions[kernel_ix].name()
+        ));
+        ui.separator();
+        if !matches!(
+            self.config.sim_mode,
+            SimMode::Discrete | SimMode::SecondOrder
+        ) {
+            ui.label("only the discrete and the second order mode use rules");
+            return;
+        }
+        let mask = self.masks[self.vec_ix].index(ix);
+        if !mask.is_open() {
+            let next = mask.forced_cell().unwrap_or(state);
+            ui.label(format!("the cell is masked, next state: {next}"));
+            return;
+        }
+        let next = self.predict_ui(ui, ix, state, acc, age);
+        if let Some(next) = next {
+            let next = if self.config.sim_mode == SimMode::SecondOrder {
+                let prev = self.second_order.prev.index(ix);
+                let combined = self.second_order.combine(next, prev);
+                ui.label(format!(
+                    "combined with the previous state {prev}: {combined}"
+                ));
+                combined
+            } else {
+                next
+            };
+            ui.label(RichText::new(format!("next state: {next}")).strong());
+        }
+    }
+
+    /// explains the result of the transition function, None if it depends on chance
+    fn predict_ui(
+        &self,
+        ui: &mut Ui,
+        ix: (usize, usize),
+        state: CellType,
+        acc: FieldType,
+        age: Option<u32>,
+    ) -> Option<CellType> {
+        let bdiscrete = self.config.sim_mode == SimMode::Discrete;
+        if let Some((name, update_rule)) =
+            self.config
+                .update_rule
+                .filter(|_| bdiscrete)
+                .and_then(|rule_ix| {
+                    Some((
+                        self.rule_registry.names().nth(rule_ix)?,
+                        self.rule_registry.get(rule_ix)?,
+                    ))
+                })
+        {
+            ui.label(format!("the update rule {name} decides"));
+            let ctx = NeighbourhoodCtx {
+                position: ix,
+                generation: self.generation,
+                age,
+                cells: &self.cell_type_vec[self.vec_ix],
+                topology: self.config.topology,
+            };
+            return Some(update_rule.apply(state, acc, &ctx));
+        }
+        if bdiscrete && self.config.bscript_rules && self.script.has_rule() {
+            ui.label("the rule script decides");
+            return match self
+                .script
+                .next(&mut Scope::new(), state, acc, ix, self.generation)
+            {
+                Ok(next) => Some(next),
+                Err(err) => {
+                    ui.colored_label(Color32::RED, err);
+                    None
+                }
+            };
+        }
+        let region = self.region_maps[self.vec_ix].index(ix);
+        let rules = Self::region_rules(&self.rules, &self.regions, region);
+        let bprobabilities = bdiscrete && self.config.brandom_rules;
+        let num_other = rules
+            .rules
+            .iter()
+            .filter(|rule| rule.state != state)
+            .count();
+        let mut o_first = None;
+        for (rule_ix, rule) in rules
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.state == state)
+        {
+            match rule.mismatch(state, acc, age) {
+                Some(reason) => {
+                    ui.label(format!("rule {rule_ix}: {reason}"));
+                }
+                None => {
+                    ui.colored_label(
+                        Color32::GREEN,
+                        format!("rule {rule_ix} matches: {state} -> {}", rule.transition),
+                    );
+                    o_first = Some(rule_ix);
+                    if !rules.bcompeting {
+                        break;
+                    }
+                }
+            }
+        }
+        if num_other > 0 {
+            ui.label(format!("{num_other} rules are for other states"));
+        }
+        let first = match o_first {
+            Some(first) => first,
+            None => {
+                ui.label("no rule matches, the state stays the same");
+                return Some(state);
+            }
+        };
+        if rules.is_random(state, acc, age, bprobabilities) {
+            if rules.bcompeting && bprobabilities {
+                ui.label("the matching rules compete by weight, the state stays with the weight left to 1");
+            } else if rules.bcompeting {
+                ui.label("one of the matching rules is picked by chance");
+            } else if !(bprobabilities && rules.rules[first].transition_probability.is_some()) {
+                ui.label(format!(
+                    "rule {first} fires, its transition is drawn from the weighted transitions"
+                ));
+            } else {
+                ui.label(format!(
+                    "rule {first} fires by chance, otherwise the next matching rule is tried"
+                ));
+            }
+            return None;
+        }
+        Some(if bprobabilities {
+            rules.apply_random(state, acc, age)
+        } else {
+            rules.apply(state, acc, age)
+        })
+    }
+}
@@ -1,21 +1,43 @@
 use super::*;
 use crate::{
-    app_config::AppConfig,
+    activity_map::ActivityMap,
+    age::CellAge,
+    app_config::{AppConfig, SimMode, UpdateSchedule},
+    breakpoint::{Breakpoints, StepResult},
     cell_type::{CellType, CellTypeMap},
-    convolution::Convolution,
+    color::{Color, RED},
+    continuous::ContinuousParams,
+    convolution::{Convolution, KernelParams},
+    elementary::OneDim,
     fade::Fader,
+    fire_counts::FireCounts,
     index_set::IndexSet,
-    quad_tree::QuadTree,
-    rules::classic_rules,
+    margolus::Margolus,
+    mask::Mask,
+    reaction_diffusion::ReactionDiffusion,
+    reduction::Reducer,
+    region::{region_overlay, Region},
+    rules::{classic_rules, RuleSet},
     save_file::ConvMatrixE,
+    script::{Script, ScriptCommand},
+    second_order::SecondOrder,
+    self_check::SelfCheck,
+    timeline::{Timeline, TimelineEvent},
+    topology::Topology,
     traits::ConvolutionT,
-    BaseMatrix, FieldType, RState, CELLS,
+    update_rule::{NeighbourhoodCtx, RuleRegistry},
+    BaseMatrix, ConvolutionMatrix, FieldType, RState, CELLS,
 };
 use egui_dock::Tree;
 use instant::Instant;
 use matrices::traits::*;
 use matrices::{const_matrix::ConstMatrix, vec_matrix::VecMatrix};
-use std::ops::RangeInclusive;
+use quad_rand::gen_range;
+use rhai::Scope;
+use std::{ops::RangeInclusive, time::Duration};
+
+/// the automation script stops stepping for the frame after this time so the UI stays responsive
+const SCRIPT_FRAME_BUDGET: Duration = Duration::from_millis(15);
 
 impl<const CW: usize> RState<CW> {
     pub fn new() -> Self {
@@ -41,6 +63,14 @@ impl<const CW: usize> RState<CW> {
             }
             (f, ct, acc)
         };
+        let masks = CELLS
+            .iter()
+            .map(|(cw, ch)| VecMatrix::new(*cw, *ch, Mask::Open))
+            .collect();
+        let region_maps = CELLS
+            .iter()
+            .map(|(cw, ch)| VecMatrix::new(*cw, *ch, 0))
+            .collect();
         let mut tabs = Vec::new();
         for i in 0..9 {
             tabs.push(i);
@@ -50,17 +80,39 @@ impl<const CW: usize> RState<CW> {
             conv_kernels: [conv_matrix; 9],
             cell_type_map,
             rules: classic_rules(),
+            continuous: ContinuousParams::default(),
+            reaction_diffusion: ReactionDiffusion::new(
+                CELLS[fields_vec_ix].0,
+                CELLS[fields_vec_ix].1,
+            ),
+            one_dim: OneDim::default(),
+            margolus: Margolus::default(),
+            second_order: SecondOrder::new(CELLS[fields_vec_ix].0, CELLS[fields_vec_ix].1),
+            age: CellAge::new(CELLS[fields_vec_ix].0, CELLS[fields_vec_ix].1),
+            timeline: Timeline::default(),
+            script: Script::new(),
+            rule_registry: RuleRegistry::with_builtins(),
+            fire_counts: FireCounts::default(),
+            breakpoints: Breakpoints::default(),
+            self_check: SelfCheck::default(),
             fields_vec,
             cell_type_vec,
+            masks,
+            region_maps,
+            regions: Vec::new(),
+            interaction: None,
             acc_vec,
             vec_ix: fields_vec_ix,
             hover_ix: None,
             fader: Fader::new(CELLS[fields_vec_ix].0, CELLS[fields_vec_ix].1),
             config: AppConfig::default(),
-            quad_tree: QuadTree::new(CELLS[fields_vec_ix].0, CELLS[fields_vec_ix].1, 5),
+            activity: ActivityMap::new(CELLS[fields_vec_ix].0, CELLS[fields_vec_ix].1, CW),
+            pending: IndexSet::new(CELLS[fields_vec_ix].0, CELLS[fields_vec_ix].1),
+            generation: 0,
             inst: Instant::now(),
             frame_time: 0.,
             save_file: None,
+            load_error: None,
             ui_up_arrow: None,
             ui_down_arrow: None,
             tree,
@@ -70,73 +122,743 @@ impl<const CW: usize> RState<CW> {
     pub fn step(&mut self) {
         self.config.bupdate = true;
         self.config.tick = Instant::now();
+        self.generation += 1;
+        self.fire_counts.start_generation(
+            std::iter::once(self.rules.rules.len())
+                .chain(self.regions.iter().map(|region| region.rules.rules.len())),
+        );
+        self.breakpoints
+            .before_step(&self.cell_type_vec[self.vec_ix]);
+        match self.config.sim_mode {
+            SimMode::Discrete => self.step_discrete(),
+            SimMode::Continuous => self.step_continuous(),
+            SimMode::ReactionDiffusion => self.reaction_diffusion.step(
+                &self.cell_type_vec[self.vec_ix],
+                &self.masks[self.vec_ix],
+                self.config.topology,
+            ),
+            SimMode::OneDimensional => self.one_dim.step(
+                &mut self.cell_type_vec[self.vec_ix],
+                &self.masks[self.vec_ix],
+            ),
+            SimMode::Margolus => self.margolus.step(
+                &mut self.cell_type_vec[self.vec_ix],
+                &self.masks[self.vec_ix],
+            ),
+            SimMode::SecondOrder => self.step_second_order(),
+        }
+        if self.config.bself_check && self.config.sim_mode != SimMode::Discrete {
+            self.self_check.skipped = Some("only the discrete mode is stepped incrementally");
+        }
+        // only the discrete mode tracks changed cells in the activity map,
+        // the changes of the timeline are not changes of the simulation
+        let bchanged = self.config.sim_mode != SimMode::Discrete || self.activity.has_changes();
+        self.run_timeline();
+        let step_result = StepResult {
+            generation: self.generation,
+            cells: &self.cell_type_vec[self.vec_ix],
+            bchanged,
+            fire_counts: &self.fire_counts,
+        };
+        if self.breakpoints.after_step(&step_result).is_some() {
+            self.config.paused = true;
+        }
+        self.config.elapsed = self.config.tick.elapsed();
+    }
+
+    /// steps until a breakpoint is hit or `max_generations` steps are done,
+    /// returns the index of the breakpoint which was hit
+    pub fn run_until_break(&mut self, max_generations: usize) -> Option<usize> {
+        self.breakpoints.last_hit = None;
+        for _ in 0..max_generations {
+            self.step();
+            if let Some((ix, _)) = self.breakpoints.last_hit {
+                return Some(ix);
+            }
+        }
+        None
+    }
+
+    /// runs the keyframes and tracks of the timeline for the current generation
+    fn run_timeline(&mut self) {
+        let events: Vec<TimelineEvent> =
+            self.timeline.events_at(self.generation).cloned().collect();
+        for event in events {
+            match event {
+                TimelineEvent::SetRules(rules) => {
+                    self.rules = rules;
+                    self.fire_counts.reset();
+                    self.everything_changed();
+                }
+                TimelineEvent::Stamp { position, cells } => self.stamp(position, &cells),
+                TimelineEvent::Pause => self.config.paused = true,
+            }
+        }
+        let values: Vec<(CellType, FieldType)> = self.timeline.values_at(self.generation).collect();
+        for (cell_type, value) in values {
+            if self.cell_type_map[cell_type].1 != value {
+                self.cell_type_map.set_value(cell_type, value);
+                self.value_changed_for(cell_type);
+            }
+        }
+    }
+
+    /// runs the automation script and then the commands it issued,
+    /// commands which do not fit into this frame run in the next frames
+    pub fn run_automation_script(&mut self) {
+        let commands = match self.script.run_automation(self.generation) {
+            Ok(commands) => commands,
+            Err(err) => {
+                self.script.error = Some(err);
+                return;
+            }
+        };
+        self.script.error = None;
+        self.script.queue = commands.into();
+        self.run_script_commands();
+    }
+
+    /// runs the queued commands of the automation script until the frame budget is used up,
+    /// at least one step is done in every frame
+    pub fn run_script_commands(&mut self) {
+        let start = Instant::now();
+        let mut bstepped = false;
+        while let Some(command) = self.script.queue.pop_front() {
+            match command {
+                ScriptCommand::Step(n) => {
+                    let mut done = 0;
+                    while done < n && !(bstepped && start.elapsed() > SCRIPT_FRAME_BUDGET) {
+                        self.step();
+                        done += 1;
+                        bstepped = true;
+                    }
+                    if done < n {
+                        self.script.queue.push_front(ScriptCommand::Step(n - done));
+                        break;
+                    }
+                }
+                ScriptCommand::SetCell((x, y), cell) => {
+                    if self.is_valid_index(x, y) && self.masks[self.vec_ix].index((x, y)).is_open()
+                    {
+                        self.set_at_index(x, y, &cell);
+                    }
+                }
+                ScriptCommand::Stamp(position, cells) => self.stamp(position, &cells),
+                ScriptCommand::SetValue(cell_type, value) => {
+                    self.cell_type_map.set_value(cell_type, value);
+                    self.value_changed_for(cell_type);
+                }
+                ScriptCommand::Pause => self.config.paused = true,
+            }
+        }
+        self.config.bupdate = true;
+    }
+
+    /// copies the non empty cells of `cells` onto the board, masked cells are skipped
+    pub fn stamp(&mut self, (x, y): (usize, usize), cells: &VecMatrix<CellType>) {
+        for ixy in 0..cells.height() {
+            for ixx in 0..cells.width() {
+                let cell = cells.index((ixx, ixy));
+                let (bx, by) = (x + ixx, y + ixy);
+                if cell == CellType::NoCell
+                    || !self.is_valid_index(bx, by)
+                    || !self.masks[self.vec_ix].index((bx, by)).is_open()
+                {
+                    continue;
+                }
+                self.set_at_index(bx, by, &cell);
+                if self.config.btrack_age {
+                    self.age.transition((bx, by), self.generation);
+                }
+            }
+        }
+        self.config.bupdate = true;
+    }
+
+    /// the cells of the rectangle at `(x, y)` clipped to the board
+    pub fn copy_cells(
+        &self,
+        (x, y): (usize, usize),
+        (w, h): (usize, usize),
+    ) -> VecMatrix<CellType> {
+        let board = self.get_cells();
+        let w = w.min(board.width().saturating_sub(x));
+        let h = h.min(board.height().saturating_sub(y));
+        let mut res = VecMatrix::new(w, h, CellType::NoCell);
+        for ixy in 0..h {
+            for ixx in 0..w {
+                res.set_at_index((ixx, ixy), board.index((x + ixx, y + ixy)));
+            }
+        }
+        res
+    }
+
+    /// restarts the generation counter and with it the timeline and the rule fire counters
+    pub fn reset_generation(&mut self) {
+        self.generation = 0;
+        self.fire_counts.reset();
+        let (w, h) = CELLS[self.vec_ix];
+        self.age.reset(w, h, self.generation);
+    }
+
+    /// the cells after the next discrete step, evaluated on the whole board without the activity map
+    /// has to be called after `generation` was increased, the state is not changed
+    /// returns why the step can not be computed if it involves chance
+    pub fn reference_step(&self) -> Result<VecMatrix<CellType>, &'static str> {
+        let update_rule = self
+            .config
+            .update_rule
+            .and_then(|ix| self.rule_registry.get(ix));
+        if std::iter::once(&self.rules)
+            .chain(self.regions.iter().map(|region| &region.rules))
+            .any(|rules| rules.involves_chance(self.config.brandom_rules))
+        {
+            return Err("the outcome of the rules can depend on chance");
+        }
+        match self.config.schedule {
+            UpdateSchedule::RandomSequential | UpdateSchedule::Alpha => {
+                return Err("the update schedule is random")
+            }
+            UpdateSchedule::Synchronous | UpdateSchedule::Checkerboard => {}
+        }
+        if update_rule.is_some_and(|rule| rule.bevery_cell()) {
+            return Err("the update rule can depend on chance");
+        }
+        let fields = &self.fields_vec[self.vec_ix];
+        let cells = &self.cell_type_vec[self.vec_ix];
+        let mask = &self.masks[self.vec_ix];
+        let region_map = &self.region_maps[self.vec_ix];
+        let mut res = cells.clone();
+        let mut reducers = Reducer::all(&self.config.reductions);
+        let mut scope = Scope::new();
+        for ixy in 0..cells.height() {
+            for ixx in 0..cells.width() {
+                if !mask.index((ixx, ixy)).is_open()
+                    || (self.config.schedule == UpdateSchedule::Checkerboard
+                        && (ixx + ixy) % 2 != self.generation % 2)
+                {
+                    continue;
+                }
+                let region = region_map.index((ixx, ixy));
+                let params = KernelParams {
+                    kernels: Self::region_kernels(&self.conv_kernels, &self.regions, region),
+                    bsingle_kernel: self.config.bsingle_kernel,
+                    interaction: self.interaction.as_ref(),
+                    reductions: &self.config.reductions,
+                    topology: self.config.topology,
+                    bfixed_border: false,
+                };
+                let acc = fields.convolution_at(&params, cells, (ixx, ixy), &mut reducers);
+                let cell = cells.index((ixx, ixy));
+                let age = self
+                    .config
+                    .btrack_age
+                    .then(|| self.age.age((ixx, ixy), self.generation));
+                let next = if let Some(update_rule) = update_rule {
+                    let ctx = NeighbourhoodCtx {
+                        position: (ixx, ixy),
+                        generation: self.generation,
+                        age,
+                        cells,
+                        topology: self.config.topology,
+                    };
+                    update_rule.apply(cell, acc, &ctx)
+                } else if self.config.bscript_rules && self.script.has_rule() {
+                    self.script
+                        .next(&mut scope, cell, acc, (ixx, ixy), self.generation)
+                        .unwrap_or(cell)
+                } else {
+                    Self::region_rules(&self.rules, &self.regions, region).apply(cell, acc, age)
+                };
+                res.set_at_index((ixx, ixy), next);
+            }
+        }
+        Ok(res)
+    }
+
+    fn step_discrete(&mut self) {
+        let reference = self.config.bself_check.then(|| self.reference_step());
         let field_type_matrix = &mut self.fields_vec[self.vec_ix];
         let cell_type_matrix = &mut self.cell_type_vec[self.vec_ix];
         let acc_matrix = &mut self.acc_vec[self.vec_ix];
+        let mask = &self.masks[self.vec_ix];
+        let region_map = &self.region_maps[self.vec_ix];
+        let update_rule = self
+            .config
+            .update_rule
+            .and_then(|ix| self.rule_registry.get(ix));
         let indices = {
             let mut res = IndexSet::new(acc_matrix.width(), acc_matrix.height());
-            if self.config.brandom_rules {
+            let range_vec = self.activity.changed_ranges(CW);
+            // dbg!(&range_vec);
+            for range in range_vec.iter() {
+                res.insert_rect(range);
+            }
+            res.union_with(&self.pending);
+            if (self.config.bscript_rules && self.script.has_rule())
+                || update_rule.is_some_and(|rule| rule.bevery_cell())
+            {
+                // scripts can depend on the coordinates and the generation
                 res.insert_rect(&((0, acc_matrix.width() - 1)..=(0, acc_matrix.height() - 1)));
-            } else {
-                let mut range_vec = Vec::new();
-                self.quad_tree.get_changed_ranges(CW, 0, 0, &mut range_vec);
-                // dbg!(&range_vec);
-                for range in range_vec.iter() {
-                    res.insert_rect(range);
-                }
             }
             res
         };
-        field_type_matrix.convolution(
-            &self.conv_kernels,
-            self.config.bsingle_kernel,
-            cell_type_matrix,
-            acc_matrix,
-            &indices,
-        );
+        let mut script_error = None;
+        let mut scope = Scope::new();
+        self.activity.clear();
+        self.pending.clear();
+        // cells which are not updated by the schedule stay pending
+        let schedule = self.config.schedule;
+        let indices = match schedule {
+            UpdateSchedule::Synchronous | UpdateSchedule::RandomSequential => indices,
+            UpdateSchedule::Checkerboard | UpdateSchedule::Alpha => {
+                let mut active = IndexSet::new(acc_matrix.width(), acc_matrix.height());
+                for (ixx, ixy) in indices.iter() {
+                    let bactive = match schedule {
+                        UpdateSchedule::Checkerboard => (ixx + ixy) % 2 == self.generation % 2,
+                        _ => gen_range(0.0, 1.0) < self.config.update_probability,
+                    };
+                    if bactive {
+                        active.insert((ixx, ixy));
+                    } else {
+                        self.pending.insert((ixx, ixy));
+                    }
+                }
+                active
+            }
+        };
+        // update rules can look at the neighbours, under the synchronous schedules
+        // they have to see the board from before the step
+        let before = (update_rule.is_some() && schedule != UpdateSchedule::RandomSequential)
+            .then(|| cell_type_matrix.clone());
+        let mut reducers = Reducer::all(&self.config.reductions);
+        let shuffled;
+        let order: Box<dyn Iterator<Item = (usize, usize)>> =
+            if schedule == UpdateSchedule::RandomSequential {
+                shuffled = Self::shuffled(&indices);
+                Box::new(shuffled.iter().copied())
+            } else {
+                for (region, region_indices) in
+                    Self::split_by_region(&indices, region_map, &self.regions)
+                {
+                    let params = KernelParams {
+                        kernels: Self::region_kernels(&self.conv_kernels, &self.regions, region),
+                        bsingle_kernel: self.config.bsingle_kernel,
+                        interaction: self.interaction.as_ref(),
+                        reductions: &self.config.reductions,
+                        topology: self.config.topology,
+                        bfixed_border: false,
+                    };
+                    field_type_matrix.convolution(
+                        &params,
+                        cell_type_matrix,
+                        acc_matrix,
+                        &region_indices,
+                    );
+                }
+                Box::new(indices.iter())
+            };
         // dbg!(&indices);
-        // self.quad_tree.print_levels();
         // println!("Acc:\n{acc_matrix}");
-        self.quad_tree.clear();
         // map the accumulated values to the cell matrix
         // field_type_matrix -> self.rules.apply(...) -> self.cell_type_vec[self.vec_ix]
         // self.cell_type_vec[self.vec_ix] -> self.map.lookup(...) -> self.fields_vec[self.vec_ix]
         // stores indices that have already had rules applied to them
-        for (ixx, ixy) in indices.iter() {
-            let acc = acc_matrix.index((ixx, ixy));
+        for (ixx, ixy) in order {
+            // masked cells never change
+            if !mask.index((ixx, ixy)).is_open() {
+                continue;
+            }
+            let region = region_map.index((ixx, ixy));
+            let rules = Self::region_rules(&self.rules, &self.regions, region);
+            let acc = if schedule == UpdateSchedule::RandomSequential {
+                // the fields already contain the cells updated before in this step
+                let params = KernelParams {
+                    kernels: Self::region_kernels(&self.conv_kernels, &self.regions, region),
+                    bsingle_kernel: self.config.bsingle_kernel,
+                    interaction: self.interaction.as_ref(),
+                    reductions: &self.config.reductions,
+                    topology: self.config.topology,
+                    bfixed_border: false,
+                };
+                let acc = field_type_matrix.convolution_at(
+                    &params,
+                    cell_type_matrix,
+                    (ixx, ixy),
+                    &mut reducers,
+                );
+                acc_matrix.set_at_index((ixx, ixy), acc);
+                acc
+            } else {
+                acc_matrix.index((ixx, ixy))
+            };
             let initial_cell = cell_type_matrix.index((ixx, ixy));
-            let cell = if self.config.brandom_rules {
-                self.rules.apply_random(initial_cell, acc)
+            let age = self
+                .config
+                .btrack_age
+                .then(|| self.age.age((ixx, ixy), self.generation));
+            let cell = if let Some(update_rule) = update_rule {
+                let ctx = NeighbourhoodCtx {
+                    position: (ixx, ixy),
+                    generation: self.generation,
+                    age,
+                    cells: before.as_ref().unwrap_or(cell_type_matrix),
+                    topology: self.config.topology,
+                };
+                update_rule.apply(initial_cell, acc, &ctx)
+            } else if self.config.bscript_rules && self.script.has_rule() {
+                if script_error.is_some() {
+                    initial_cell
+                } else {
+                    self.script
+                        .next(&mut scope, initial_cell, acc, (ixx, ixy), self.generation)
+                        .unwrap_or_else(|err| {
+                            script_error = Some(err);
+                            initial_cell
+                        })
+                }
             } else {
-                self.rules.apply(initial_cell, acc)
+                let bprobabilities = self.config.brandom_rules;
+                let fired = if bprobabilities {
+                    rules.firing_rule_random(initial_cell, acc, age)
+                } else {
+                    rules.firing_rule(initial_cell, acc, age)
+                };
+                if let Some(rule) = fired {
+                    self.fire_counts.fire(region as usize, rule);
+                }
+                // weighted transitions and competing rules are drawn on both paths
+                let cell = fired.map_or(initial_cell, |rule| rules.rules[rule].random_transition());
+                if cell == initial_cell && rules.is_random(initial_cell, acc, age, bprobabilities) {
+                    self.pending.insert((ixx, ixy));
+                }
+                cell
             };
             let field = self.cell_type_map[cell].1;
             if cell != initial_cell {
                 cell_type_matrix.set_at_index((ixx, ixy), cell);
                 field_type_matrix.set_at_index((ixx, ixy), field);
-                self.quad_tree.insert(ixx, ixy, 0, 0);
+                self.activity.insert(ixx, ixy);
+                if self.config.btrack_age {
+                    self.age.transition((ixx, ixy), self.generation);
+                }
+            } else if self.config.btrack_age && rules.has_age_rules(cell) {
+                // the age changes even if the neighbourhood stays the same
+                self.pending.insert((ixx, ixy));
             }
         }
         if self.config.bfade {
             self.fader
                 .add(&self.cell_type_vec[self.vec_ix], &self.cell_type_map);
         }
-        self.config.elapsed = self.config.tick.elapsed();
+        if script_error.is_some() {
+            self.script.error = script_error;
+            self.config.paused = true;
+        }
+        match reference {
+            Some(Ok(reference)) => {
+                let bsame = self.self_check.compare(
+                    &self.cell_type_vec[self.vec_ix],
+                    &reference,
+                    self.generation,
+                );
+                // the differences are shown on the board
+                self.config.paused |= !bsame;
+            }
+            Some(Err(reason)) => self.self_check.skipped = Some(reason),
+            None => {}
+        }
+    }
+
+    /// the indices grouped by the region of the cells,
+    /// cells of regions which do not exist belong to region 0
+    fn split_by_region(
+        indices: &IndexSet,
+        region_map: &VecMatrix<u8>,
+        regions: &[Region<ConvolutionMatrix<CW>>],
+    ) -> Vec<(u8, IndexSet)> {
+        if regions.is_empty() {
+            return vec![(0, indices.clone())];
+        }
+        let (w, h) = (region_map.width(), region_map.height());
+        let mut res: Vec<(u8, IndexSet)> = (0..=regions.len())
+            .map(|region| (region as u8, IndexSet::new(w, h)))
+            .collect();
+        for ix in indices.iter() {
+            let region = region_map.index(ix) as usize;
+            let region = if region <= regions.len() { region } else { 0 };
+            res[region].1.insert(ix);
+        }
+        res
+    }
+
+    pub(super) fn region_kernels<'a>(
+        kernels: &'a [ConvolutionMatrix<CW>; 9],
+        regions: &'a [Region<ConvolutionMatrix<CW>>],
+        region: u8,
+    ) -> &'a [ConvolutionMatrix<CW>; 9] {
+        (region as usize)
+            .checked_sub(1)
+            .and_then(|ix| regions.get(ix))
+            .and_then(|region| region.kernels.as_ref())
+            .unwrap_or(kernels)
+    }
+
+    pub(super) fn region_rules<'a>(
+        rules: &'a RuleSet<FieldType>,
+        regions: &'a [Region<ConvolutionMatrix<CW>>],
+        region: u8,
+    ) -> &'a RuleSet<FieldType> {
+        (region as usize)
+            .checked_sub(1)
+            .and_then(|ix| regions.get(ix))
+            .map(|region| &region.rules)
+            .unwrap_or(rules)
+    }
+
+    /// the indices in random order
+    fn shuffled(indices: &IndexSet) -> Vec<(usize, usize)> {
+        let mut res: Vec<(usize, usize)> = indices.iter().collect();
+        for ix in (1..res.len()).rev() {
+            res.swap(ix, gen_range(0, ix + 1));
+        }
+        res
+    }
+
+    /// every cell is updated with `x += dt * growth(kernel * field)`
+    fn step_continuous(&mut self) {
+        let field_type_matrix = &mut self.fields_vec[self.vec_ix];
+        let cell_type_matrix = &self.cell_type_vec[self.vec_ix];
+        let acc_matrix = &mut self.acc_vec[self.vec_ix];
+        let mut indices = IndexSet::new(acc_matrix.width(), acc_matrix.height());
+        indices.insert_rect(&((0, acc_matrix.width() - 1)..=(0, acc_matrix.height() - 1)));
+        let params = KernelParams {
+            kernels: &self.conv_kernels,
+            bsingle_kernel: true,
+            interaction: None,
+            reductions: &self.config.reductions,
+            topology: self.config.topology,
+            bfixed_border: false,
+        };
+        field_type_matrix.convolution(&params, cell_type_matrix, acc_matrix, &indices);
+        let mask = &self.masks[self.vec_ix];
+        for (ixx, ixy) in indices.iter() {
+            let value = field_type_matrix.index((ixx, ixy));
+            let next = match mask.index((ixx, ixy)) {
+                Mask::Open => self.continuous.update(value, acc_matrix.index((ixx, ixy))),
+                cell_mask => cell_mask.forced_value().unwrap_or(value),
+            };
+            field_type_matrix.set_at_index((ixx, ixy), next);
+        }
+    }
+
+    /// every cell is updated with `next = (rules(current) - previous) mod states`
+    /// probabilistic rules are not used, they would break the reversibility
+    fn step_second_order(&mut self) {
+        let field_type_matrix = &mut self.fields_vec[self.vec_ix];
+        let cell_type_matrix = &mut self.cell_type_vec[self.vec_ix];
+        let acc_matrix = &mut self.acc_vec[self.vec_ix];
+        let mut indices = IndexSet::new(acc_matrix.width(), acc_matrix.height());
+        indices.insert_rect(&((0, acc_matrix.width() - 1)..=(0, acc_matrix.height() - 1)));
+        let region_map = &self.region_maps[self.vec_ix];
+        for (region, region_indices) in Self::split_by_region(&indices, region_map, &self.regions) {
+            let params = KernelParams {
+                kernels: Self::region_kernels(&self.conv_kernels, &self.regions, region),
+                bsingle_kernel: self.config.bsingle_kernel,
+                interaction: self.interaction.as_ref(),
+                reductions: &self.config.reductions,
+                topology: self.config.topology,
+                bfixed_border: false,
+            };
+            field_type_matrix.convolution(&params, cell_type_matrix, acc_matrix, &region_indices);
+        }
+        for (ixx, ixy) in indices.iter() {
+            let acc = acc_matrix.index((ixx, ixy));
+            let cell = cell_type_matrix.index((ixx, ixy));
+            let prev = self.second_order.prev.index((ixx, ixy));
+            if !self.masks[self.vec_ix].index((ixx, ixy)).is_open() {
+                self.second_order.prev.set_at_index((ixx, ixy), cell);
+                continue;
+            }
+            let age = self
+                .config
+                .btrack_age
+                .then(|| self.age.age((ixx, ixy), self.generation));
+            let region = region_map.index((ixx, ixy));
+            let rules = Self::region_rules(&self.rules, &self.regions, region);
+            let fired = rules.firing_rule(cell, acc, age);
+            if let Some(rule) = fired {
+                self.fire_counts.fire(region as usize, rule);
+            }
+            let ruled = fired.map_or(cell, |rule| rules.rules[rule].random_transition());
+            let next = self.second_order.combine(ruled, prev);
+            self.second_order.prev.set_at_index((ixx, ixy), cell);
+            if next != cell {
+                cell_type_matrix.set_at_index((ixx, ixy), next);
+                field_type_matrix.set_at_index((ixx, ixy), self.cell_type_map[next].1);
+                if self.config.btrack_age {
+                    self.age.transition((ixx, ixy), self.generation);
+                }
+            }
+        }
+    }
+
+    /// runs the second order automaton backwards from now on
+    pub fn reverse_time(&mut self) {
+        self.second_order
+            .reverse(&mut self.cell_type_vec[self.vec_ix]);
+        self.update_fields_from_cells();
+        self.everything_changed();
+    }
+
+    pub fn set_sim_mode(&mut self, sim_mode: SimMode) {
+        if self.config.sim_mode != sim_mode {
+            self.config.sim_mode = sim_mode;
+            self.one_dim.row = 0;
+            // the continuous and 1D modes do not keep the fields in sync with the cells
+            self.update_fields_from_cells();
+            self.everything_changed();
+        }
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        if self.config.topology != topology {
+            self.config.topology = topology;
+            // the cells have to be drawn with a different shape
+            self.config.bnew_size = true;
+            self.everything_changed();
+        }
+    }
+
+    /// color of the cell at (ixx, ixy) on the screen
+    pub fn color_at(&self, ixx: usize, ixy: usize) -> Color {
+        let color = match self.config.sim_mode {
+            SimMode::Discrete
+            | SimMode::OneDimensional
+            | SimMode::Margolus
+            | SimMode::SecondOrder => {
+                let cell = self.get_cells().index((ixx, ixy));
+                let age_color = if self.config.btrack_age && self.config.bcolor_by_age {
+                    self.age.color((ixx, ixy), cell, self.generation)
+                } else {
+                    None
+                };
+                if let Some(color) = age_color {
+                    color
+                } else if self.config.bfade {
+                    self.fader.index(ixx, ixy)
+                } else {
+                    self.cell_type_map[cell].0
+                }
+            }
+            SimMode::Continuous => self
+                .continuous
+                .color_ramp
+                .sample(self.get_fields().index((ixx, ixy))),
+            SimMode::ReactionDiffusion => self.reaction_diffusion.color_at(ixx, ixy),
+        };
+        let color = if self.config.bshow_regions {
+            region_overlay(self.region_maps[self.vec_ix].index((ixx, ixy)), color)
+        } else {
+            color
+        };
+        let color = if self.config.bshow_mask {
+            self.masks[self.vec_ix].index((ixx, ixy)).overlay(color)
+        } else {
+            color
+        };
+        let color = if self.config.bself_check && self.self_check.is_mismatch((ixx, ixy)) {
+            RED
+        } else {
+            color
+        };
+        self.footprint_overlay((ixx, ixy), color)
+    }
+
+    /// has to be called after `vec_ix` changed
+    pub fn board_size_changed(&mut self) {
+        let (w, h) = CELLS[self.vec_ix];
+        self.fader = Fader::new(w, h);
+        self.activity = ActivityMap::new(w, h, CW);
+        self.pending = IndexSet::new(w, h);
+        self.reaction_diffusion.resize(w, h);
+        self.second_order.reset(w, h);
+        self.age.reset(w, h, self.generation);
+        self.config.bnew_size = true;
+        self.config.bupdate = true;
     }
 
     pub fn randomize(&mut self, range: RangeInclusive<CellType>) {
+        let w = self.fields_vec[self.vec_ix].width();
+        let h = self.fields_vec[self.vec_ix].height();
+        let masked = self.masked_cells();
+        match self.config.sim_mode {
+            SimMode::Discrete | SimMode::Margolus | SimMode::SecondOrder => {
+                self.cell_type_vec[self.vec_ix] = VecMatrix::new_random_range(w, h, range);
+                self.update_fields_from_cells();
+                self.second_order.reset(w, h);
+                self.age.reset(w, h, self.generation);
+            }
+            SimMode::Continuous => {
+                let start = self.cell_type_map[*range.start()].1.clamp(0.0, 1.0);
+                let end = self.cell_type_map[*range.end()].1.clamp(0.0, 1.0);
+                self.fields_vec[self.vec_ix] = Convolution::new_random_range(w, h, start..=end);
+            }
+            SimMode::ReactionDiffusion => self.reaction_diffusion.seed(),
+            SimMode::OneDimensional => {
+                self.one_dim
+                    .seed_random(&mut self.cell_type_vec[self.vec_ix]);
+            }
+        }
+        self.restore_masked(masked);
+        self.activity.everything_changed();
+        self.config.bupdate = true;
+    }
+
+    /// state of the masked cells of the current board
+    fn masked_cells(&self) -> Vec<((usize, usize), Mask, CellType, FieldType)> {
+        let mask = &self.masks[self.vec_ix];
+        let mut res = Vec::new();
+        for ixy in 0..mask.height() {
+            for ixx in 0..mask.width() {
+                let cell_mask = mask.index((ixx, ixy));
+                if !cell_mask.is_open() {
+                    res.push((
+                        (ixx, ixy),
+                        cell_mask,
+                        self.get_cells().index((ixx, ixy)),
+                        self.get_fields().index((ixx, ixy)),
+                    ));
+                }
+            }
+        }
+        res
+    }
+
+    /// restores the masked cells after the board has been overwritten
+    /// walls get their previous state, sources and sinks their forced state
+    fn restore_masked(&mut self, masked: Vec<((usize, usize), Mask, CellType, FieldType)>) {
+        for (ix, cell_mask, cell, value) in masked {
+            let cell = cell_mask.forced_cell().unwrap_or(cell);
+            let value = if self.config.sim_mode == SimMode::Continuous {
+                cell_mask.forced_value().unwrap_or(value)
+            } else {
+                self.cell_type_map[cell].1
+            };
+            self.cell_type_vec[self.vec_ix].set_at_index(ix, cell);
+            self.fields_vec[self.vec_ix].set_at_index(ix, value);
+        }
+    }
+
+    /// sets the fields of the current board to the values of their cell types
+    fn update_fields_from_cells(&mut self) {
         let fields = &mut self.fields_vec[self.vec_ix];
-        let cells = &mut self.cell_type_vec[self.vec_ix];
-        let w = fields.width();
-        let h = fields.height();
-        *cells = VecMatrix::new_random_range(w, h, range);
-        self.quad_tree.everything_changed();
-        for x in 0..w {
-            for y in 0..h {
+        let cells = &self.cell_type_vec[self.vec_ix];
+        for x in 0..fields.width() {
+            for y in 0..fields.height() {
                 fields.set_at_index((x, y), self.cell_type_map[cells.index((x, y))].1);
             }
         }
-        self.config.bupdate = true;
     }
 
     pub fn load_save_file(&mut self) {
@@ -158,15 +880,45 @@ impl<const CW: usize> RState<CW> {
             ) {
                 self.cell_type_map = map;
             }
+            if let (Some(params), true) = (
+                save_file.reaction_diffusion.take(),
+                save_file.include_reaction_diffusion,
+            ) {
+                self.reaction_diffusion.params = params;
+                if let Some(kernels) = save_file.diffusion_kernels.take() {
+                    self.reaction_diffusion.kernels = kernels;
+                }
+                self.reaction_diffusion.seed();
+            }
             if let (Some(cells), true) = (save_file.cells.take(), save_file.include_cells) {
                 self.cell_type_vec = cells;
             }
+            if let (Some(masks), true) = (save_file.masks.take(), save_file.include_masks) {
+                self.masks = masks;
+            }
+            if let (Some(timeline), true) = (save_file.timeline.take(), save_file.include_timeline)
+            {
+                self.timeline = timeline;
+            }
+            if let (Some(scripts), true) = (save_file.scripts.take(), save_file.include_scripts) {
+                self.script.sources = scripts;
+                self.script.compile_rule();
+            }
+            if let (Some(regions), Some(region_maps), true) = (
+                save_file.regions.take(),
+                save_file.region_maps.take(),
+                save_file.include_regions,
+            ) {
+                self.regions = regions;
+                self.region_maps = region_maps;
+                self.config.selected_region = 0;
+            }
             for (fields, cells) in self
                 .fields_vec
                 .iter_mut()
                 .zip(self.cell_type_vec.iter_mut())
             {
-                self.quad_tree.everything_changed();
+                self.activity.everything_changed();
                 let w = fields.width();
                 let h = fields.height();
                 for x in 0..w {
@@ -175,6 +927,7 @@ impl<const CW: usize> RState<CW> {
                     }
                 }
             }
+            self.fire_counts.reset();
             self.config.bupdate = true;
             self.save_file = None;
         }
@@ -187,21 +940,43 @@ impl<const CW: usize> RState<CW> {
     }
 
     pub fn clear(&mut self) {
+        let masked = if self.config.bclear_mask {
+            let (w, h) = CELLS[self.vec_ix];
+            self.masks[self.vec_ix] = VecMatrix::new(w, h, Mask::Open);
+            Vec::new()
+        } else {
+            self.masked_cells()
+        };
         let fields = &mut self.fields_vec[self.vec_ix];
         let cells = &mut self.cell_type_vec[self.vec_ix];
         let field_value = self.cell_type_map[self.config.clear_val].1;
         cells.clear(self.config.clear_val);
         fields.clear(field_value);
-        self.quad_tree.everything_changed();
+        self.one_dim.row = 0;
+        self.second_order.reset(cells.width(), cells.height());
+        self.age
+            .reset(cells.width(), cells.height(), self.generation);
+        self.restore_masked(masked);
+        self.activity.everything_changed();
         self.config.bupdate = true;
     }
 
     pub fn value_changed_for(&mut self, cell_type: CellType) {
         let cells = &self.cell_type_vec[self.vec_ix];
+        let fields = &mut self.fields_vec[self.vec_ix];
+        // the fields of the continuous modes do not follow the cell types
+        let bupdate_fields = !matches!(
+            self.config.sim_mode,
+            SimMode::Continuous | SimMode::ReactionDiffusion
+        );
+        let value = self.cell_type_map[cell_type].1;
         for ixx in 0..cells.width() {
             for ixy in 0..cells.height() {
                 if cells.index((ixx, ixy)) == cell_type {
-                    self.quad_tree.insert(ixx, ixy, 0, 0);
+                    if bupdate_fields {
+                        fields.set_at_index((ixx, ixy), value);
+                    }
+                    self.activity.insert(ixx, ixy);
                 }
             }
         }
@@ -209,7 +984,7 @@ impl<const CW: usize> RState<CW> {
     }
 
     pub fn everything_changed(&mut self) {
-        self.quad_tree.everything_changed();
+        self.activity.everything_changed();
         self.config.bupdate = true;
     }
 
@@ -224,15 +999,93 @@ impl<const CW: usize> RState<CW> {
     pub fn set_at_index(&mut self, ixx: usize, ixy: usize, cell: &CellType) {
         self.fields_vec[self.vec_ix].set_at_index((ixx, ixy), self.cell_type_map[*cell].1);
         self.cell_type_vec[self.vec_ix].set_at_index((ixx, ixy), *cell);
-        self.quad_tree.insert(ixx, ixy, 0, 0);
+        self.activity.insert(ixx, ixy);
     }
 
     pub fn set_selected_at_index(&mut self, ixx: usize, ixy: usize) {
+        if let Some(mask) = self.config.paint_mask {
+            self.set_mask_at_index((ixx, ixy), mask);
+            return;
+        }
+        if let Some(region) = self.config.paint_region {
+            self.region_maps[self.vec_ix].set_at_index((ixx, ixy), region);
+            self.activity.insert(ixx, ixy);
+            self.config.bupdate = true;
+            return;
+        }
+        if !self.masks[self.vec_ix].index((ixx, ixy)).is_open() {
+            return;
+        }
+        if self.config.sim_mode == SimMode::ReactionDiffusion {
+            self.reaction_diffusion.set_seed_at_index((ixx, ixy));
+            return;
+        }
         self.fields_vec[self.vec_ix]
             .set_at_index((ixx, ixy), self.cell_type_map.get_selected_rules_val());
         self.cell_type_vec[self.vec_ix]
             .set_at_index((ixx, ixy), self.cell_type_map.get_selected_rules_cell());
-        self.quad_tree.insert(ixx, ixy, 0, 0);
+        self.activity.insert(ixx, ixy);
+        if self.config.btrack_age {
+            self.age.transition((ixx, ixy), self.generation);
+        }
+    }
+
+    /// walls get the selected cell type, sources emit the selected cell type
+    pub fn set_mask_at_index(&mut self, (ixx, ixy): (usize, usize), mask: Mask) {
+        let selected = self.cell_type_map.get_selected_rules_cell();
+        let mask = match mask {
+            Mask::Source(_) => Mask::Source(selected),
+            mask => mask,
+        };
+        self.masks[self.vec_ix].set_at_index((ixx, ixy), mask);
+        match mask {
+            Mask::Open => self.activity.insert(ixx, ixy),
+            Mask::Wall => self.set_at_index(ixx, ixy, &selected),
+            Mask::Source(cell) => self.set_at_index(ixx, ixy, &cell),
+            Mask::Sink => self.set_at_index(ixx, ixy, &CellType::NoCell),
+        }
+        if self.config.sim_mode == SimMode::Continuous {
+            if let Some(value) = mask.forced_value() {
+                self.fields_vec[self.vec_ix].set_at_index((ixx, ixy), value);
+            }
+        }
+        self.config.bupdate = true;
+    }
+
+    pub fn add_region(&mut self) {
+        if self.regions.len() < u8::MAX as usize {
+            self.regions.push(Region {
+                rules: self.rules.clone(),
+                kernels: None,
+            });
+        }
+    }
+
+    /// cells of the removed region belong to region 0, the following regions move down by one
+    pub fn remove_region(&mut self, region: usize) {
+        if region == 0 || region > self.regions.len() {
+            return;
+        }
+        self.regions.remove(region - 1);
+        for region_map in self.region_maps.iter_mut() {
+            for ixy in 0..region_map.height() {
+                for ixx in 0..region_map.width() {
+                    let cell_region = region_map.index((ixx, ixy)) as usize;
+                    if cell_region == region {
+                        region_map.set_at_index((ixx, ixy), 0);
+                    } else if cell_region > region {
+                        region_map.set_at_index((ixx, ixy), cell_region as u8 - 1);
+                    }
+                }
+            }
+        }
+        self.config.selected_region = self.config.selected_region.min(self.regions.len());
+        self.everything_changed();
+    }
+
+    /// number of regions including region 0
+    pub fn num_regions(&self) -> usize {
+        self.regions.len() + 1
     }
 
     pub fn is_valid_index(&self, ixx: usize, ixy: usize) -> bool {
@@ -246,3 +1099,388 @@ impl<const CW: usize> Default for RState<CW> {
         Self::new()
     }
 }
+
+/// a board of 100 x 50 random cells which compares every step with `reference_step`
+#[cfg(test)]
+fn self_checked_state() -> RState<7> {
+    let mut state = RState::<7>::new();
+    state.vec_ix = 1;
+    state.board_size_changed();
+    state.config.bself_check = true;
+    state
+}
+
+/// runs `steps` generations and fails on the first one which differs from `reference_step`
+#[cfg(test)]
+fn assert_steps_match_reference(state: &mut RState<7>, steps: usize) {
+    let checked = state.self_check.generations_checked;
+    for _ in 0..steps {
+        state.step();
+        assert_eq!(state.self_check.skipped, None);
+        assert!(
+            state.self_check.mismatches.is_empty(),
+            "generation {}: {:?}",
+            state.generation,
+            state.self_check.mismatches
+        );
+    }
+    assert_eq!(state.self_check.generations_checked, checked + steps);
+}
+
+/// the 8 neighbours of the classic rules in the middle of the kernel
+#[cfg(test)]
+fn moore_kernels() -> [ConvolutionMatrix<7>; 9] {
+    let kernel = ConstMatrix::new_with(7, 7, |(x, y)| {
+        if (2..=4).contains(&x) && (2..=4).contains(&y) && (x, y) != (3, 3) {
+            1.0
+        } else {
+            0.0
+        }
+    });
+    [kernel; 9]
+}
+
+/// a cleared self checked board of `CELLS[vec_ix]` with the classic rules on the 8 neighbours
+#[cfg(test)]
+fn life_board(vec_ix: usize) -> RState<7> {
+    let mut state = self_checked_state();
+    state.vec_ix = vec_ix;
+    state.board_size_changed();
+    state.conv_kernels = moore_kernels();
+    state.rules = classic_rules();
+    state.clear();
+    state
+}
+
+/// positions of the cells of type `cell`, row by row
+#[cfg(test)]
+fn cells_of(state: &RState<7>, cell: CellType) -> Vec<(usize, usize)> {
+    let cells = state.get_cells();
+    (0..cells.height())
+        .flat_map(|ixy| (0..cells.width()).map(move |ixx| (ixx, ixy)))
+        .filter(|ix| cells.index(*ix) == cell)
+        .collect()
+}
+
+/// rules under which every empty cell is born, whatever its neighbours are
+#[cfg(test)]
+fn birth_everywhere() -> RuleSet<FieldType> {
+    RuleSet::new(vec![crate::rules::Rule::new(
+        CellType::NoCell,
+        -100.0..=100.0,
+        CellType::A,
+    )])
+}
+
+#[test]
+fn update_rules_see_the_board_before_the_step() {
+    use crate::update_rule::UpdateRule;
+    struct CopyLeft;
+    impl UpdateRule for CopyLeft {
+        fn apply(&self, _state: CellType, _acc: FieldType, ctx: &NeighbourhoodCtx) -> CellType {
+            ctx.neighbour(-1, 0).unwrap_or(CellType::NoCell)
+        }
+    }
+    for schedule in [UpdateSchedule::Synchronous, UpdateSchedule::Checkerboard] {
+        for name in ["parity", "copy left"] {
+            let mut state = self_checked_state();
+            state.config.schedule = schedule;
+            state.rule_registry.register("copy left", CopyLeft);
+            state.config.update_rule = state.rule_registry.names().position(|n| n == name);
+            assert_steps_match_reference(&mut state, 5);
+        }
+    }
+}
+
+#[test]
+fn automation_script_steps_over_several_frames() {
+    let mut state = RState::<7>::new();
+    state.config.paused = false;
+    state.script.sources.automation = "set_cell(1, 2, 2);\nstep(3);\npause();\n".into();
+    state.run_automation_script();
+    while !state.script.queue.is_empty() {
+        state.run_script_commands();
+    }
+    assert_eq!(state.script.error, None);
+    assert_eq!(state.generation, 3);
+    assert_eq!(state.get_cells().index((1, 2)), CellType::B);
+    assert!(state.config.paused);
+    state.script.sources.automation = "step(1000000000);".into();
+    state.run_automation_script();
+    assert!(
+        matches!(state.script.queue.front(), Some(ScriptCommand::Step(n)) if *n < crate::script::MAX_SCRIPT_STEPS),
+        "the steps are spread over frames"
+    );
+}
+
+#[test]
+fn pending_random_cells_are_stepped_again() {
+    use crate::rules::Rule;
+    let mut state = life_board(1);
+    state.set_at_index(50, 25, &CellType::A);
+    // the neighbourhood of the lone cell never changes, only its pending rule can make it die
+    state.rules = RuleSet::new(vec![Rule {
+        transition_probability: Some(0.2),
+        ..Rule::new(CellType::A, -100.0..=100.0, CellType::NoCell)
+    }]);
+    state.config.brandom_rules = true;
+    quad_rand::srand(3);
+    let mut generations = 0;
+    while state.get_cells().index((50, 25)) == CellType::A {
+        state.step();
+        generations += 1;
+        assert!(generations < 200, "the pending cell is never stepped again");
+        if state.get_cells().index((50, 25)) == CellType::A {
+            assert_eq!(state.pending.iter().collect::<Vec<_>>(), vec![(50, 25)]);
+            assert!(!state.activity.has_changes());
+        }
+    }
+    assert!(generations > 1, "the rule fired in the first step");
+    state.step();
+    assert_eq!(state.pending.iter().count(), 0);
+}
+
+#[test]
+fn schedules_match_the_reference_or_are_skipped() {
+    for schedule in [UpdateSchedule::Synchronous, UpdateSchedule::Checkerboard] {
+        let mut state = self_checked_state();
+        state.config.schedule = schedule;
+        assert_steps_match_reference(&mut state, 6);
+    }
+    for schedule in [UpdateSchedule::RandomSequential, UpdateSchedule::Alpha] {
+        let mut state = self_checked_state();
+        state.config.schedule = schedule;
+        state.step();
+        assert!(state.self_check.skipped.is_some(), "{schedule:?}");
+        assert_eq!(state.self_check.generations_checked, 0);
+    }
+}
+
+#[test]
+fn checkerboard_steps_one_parity_per_generation() {
+    let mut state = life_board(1);
+    state.rules = birth_everywhere();
+    state.config.schedule = UpdateSchedule::Checkerboard;
+    assert_steps_match_reference(&mut state, 1);
+    let parity = state.generation % 2;
+    let born = cells_of(&state, CellType::A);
+    assert_eq!(born.len(), 2500);
+    assert!(born.iter().all(|(x, y)| (x + y) % 2 == parity));
+    assert!(state.pending.iter().all(|(x, y)| (x + y) % 2 != parity));
+    assert_eq!(state.pending.iter().count(), 2500);
+    // the other half was pending and is born in the next generation
+    assert_steps_match_reference(&mut state, 1);
+    assert_eq!(cells_of(&state, CellType::A).len(), 5000);
+    // nothing changes in the first step of the blinker, the cells of the other half
+    // only change because they stayed pending
+    let mut state = life_board(1);
+    state.config.schedule = UpdateSchedule::Checkerboard;
+    for x in 10..13 {
+        state.set_at_index(x, 10, &CellType::A);
+    }
+    assert_steps_match_reference(&mut state, 3);
+    assert_eq!(cells_of(&state, CellType::A), [(11, 9), (11, 10), (11, 11)]);
+}
+
+#[test]
+fn alpha_updates_a_fraction_of_the_cells() {
+    let mut state = life_board(1);
+    state.rules = birth_everywhere();
+    state.config.schedule = UpdateSchedule::Alpha;
+    state.config.update_probability = 0.3;
+    quad_rand::srand(5);
+    state.step();
+    assert!(state.self_check.skipped.is_some());
+    // 5000 cells, 30% of them are updated, the others stay pending
+    let born = cells_of(&state, CellType::A).len();
+    assert!((1_350..1_650).contains(&born), "{born}");
+    assert_eq!(state.pending.iter().count(), 5000 - born);
+}
+
+#[test]
+fn masked_cells_survive_step_randomize_and_clear() {
+    let mut state = life_board(1);
+    let mut paint = |(x, y), mask, cell| {
+        state.masks[state.vec_ix].set_at_index((x, y), mask);
+        state.set_at_index(x, y, &cell);
+    };
+    // the sinks next to the sources would be born and the sources would die without their masks
+    for y in 5..15 {
+        paint((30, y), Mask::Source(CellType::A), CellType::A);
+        paint((31, y), Mask::Sink, CellType::NoCell);
+    }
+    // every cell of the wall would die or be born without its mask
+    let wall_cell = |x: usize| [CellType::A, CellType::NoCell][x % 2];
+    for x in 10..90 {
+        paint((x, 20), Mask::Wall, wall_cell(x));
+    }
+    let check = |state: &RState<7>| {
+        let cells = state.get_cells();
+        let fields = &state.fields_vec[state.vec_ix];
+        for y in 5..15 {
+            assert_eq!(cells.index((30, y)), CellType::A);
+            assert_eq!(fields.index((30, y)), 1.0);
+            assert_eq!(cells.index((31, y)), CellType::NoCell);
+            assert_eq!(fields.index((31, y)), 0.0);
+        }
+        for x in 10..90 {
+            assert_eq!(cells.index((x, 20)), wall_cell(x));
+        }
+        assert_eq!(state.masked_cells().len(), 100);
+    };
+    check(&state);
+    assert_steps_match_reference(&mut state, 5);
+    check(&state);
+    state.randomize(CellType::NoCell..=CellType::A);
+    check(&state);
+    assert_steps_match_reference(&mut state, 5);
+    check(&state);
+    state.clear();
+    check(&state);
+    assert_steps_match_reference(&mut state, 5);
+    check(&state);
+    // the masks are cleared with the cells
+    state.config.bclear_mask = true;
+    state.clear();
+    assert!(state.masked_cells().is_empty());
+    assert_eq!(cells_of(&state, CellType::NoCell).len(), 5000);
+}
+
+#[test]
+fn regions_step_with_their_own_rules_and_kernels() {
+    let mut state = life_board(1);
+    state.add_region();
+    state.add_region();
+    // region 1 never changes, region 2 sees every neighbour of its large kernel
+    state.regions[0].rules = RuleSet::new(Vec::new());
+    state.regions[1].kernels = Some([ConstMatrix::new(7, 7, 0.25); 9]);
+    let region_map = &mut state.region_maps[state.vec_ix];
+    for ixy in 0..region_map.height() {
+        for ixx in 0..region_map.width() {
+            region_map.set_at_index((ixx, ixy), (ixx / 34) as u8);
+        }
+    }
+    state.randomize(CellType::NoCell..=CellType::A);
+    let frozen = state.copy_cells((34, 0), (34, 50));
+    assert_steps_match_reference(&mut state, 6);
+    let after = state.copy_cells((34, 0), (34, 50));
+    for ixy in 0..50 {
+        for ixx in 0..34 {
+            assert_eq!(after.index((ixx, ixy)), frozen.index((ixx, ixy)));
+        }
+    }
+}
+
+#[test]
+fn activity_map_steps_only_around_changes() {
+    // a glider on the largest board
+    let mut state = life_board(4);
+    for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        state.set_at_index(400 + x, 200 + y, &CellType::A);
+    }
+    for _ in 0..8 {
+        let before = cells_of(&state, CellType::A);
+        assert_steps_match_reference(&mut state, 1);
+        assert!(
+            state.activity.num_active() <= 4,
+            "{}",
+            state.activity.num_active()
+        );
+        // the tiles of the cells which were born or died are active
+        let after = cells_of(&state, CellType::A);
+        let ranges = state.activity.changed_ranges(7);
+        for (x, y) in before
+            .iter()
+            .filter(|ix| !after.contains(ix))
+            .chain(after.iter().filter(|ix| !before.contains(ix)))
+        {
+            // the ranges are `(x_start, x_end)..=(y_start, y_end)`
+            assert!(ranges.iter().any(|range| {
+                (range.start().0..=range.start().1).contains(x)
+                    && (range.end().0..=range.end().1).contains(y)
+            }));
+        }
+    }
+    // the glider moved by 2 cells
+    assert_eq!(
+        cells_of(&state, CellType::A),
+        [(403, 202), (404, 203), (402, 204), (403, 204), (404, 204)]
+    );
+    // the smallest board is a few tiles and lower than the kernel
+    let mut state = life_board(0);
+    state.randomize(CellType::NoCell..=CellType::A);
+    assert_steps_match_reference(&mut state, 10);
+}
+
+#[test]
+fn weighted_transitions_without_random_rules() {
+    use crate::rules::Rule;
+    let mut state = life_board(1);
+    state.rules = RuleSet::new(vec![Rule {
+        weighted_transitions: vec![(CellType::A, 1.0), (CellType::B, 3.0)],
+        ..Rule::new(CellType::NoCell, -100.0..=100.0, CellType::NoCell)
+    }]);
+    assert!(!state.config.brandom_rules);
+    quad_rand::srand(11);
+    state.step();
+    assert!(state.self_check.skipped.is_some());
+    // 5000 cells, a quarter become A
+    let a = cells_of(&state, CellType::A).len();
+    assert_eq!(a + cells_of(&state, CellType::B).len(), 5000);
+    assert!((1_100..1_400).contains(&a), "{a}");
+}
+
+#[test]
+fn no_change_breakpoint_with_an_active_timeline() {
+    use crate::{breakpoint::Condition, timeline::Keyframe};
+    let mut state = life_board(1);
+    // a block is a still life
+    for (x, y) in [(3, 1), (4, 1), (3, 2), (4, 2)] {
+        state.set_at_index(x, y, &CellType::A);
+    }
+    state.timeline.keyframes = (1..=3)
+        .map(|generation| Keyframe {
+            generation,
+            event: TimelineEvent::SetRules(classic_rules()),
+        })
+        .collect();
+    state.breakpoints.add(Condition::NoChange);
+    state.step();
+    assert_eq!(state.breakpoints.last_hit, Some((0, 1)));
+    assert!(state.config.paused);
+}
+
+#[test]
+fn regions_step_to_hand_computed_cells() {
+    use crate::rules::Rule;
+    let mut state = life_board(1);
+    state.add_region();
+    // region 1 grows into the cells next to it, it only sees the 4 orthogonal neighbours
+    state.regions[0].rules =
+        RuleSet::new(vec![Rule::new(CellType::NoCell, 1.0..=4.0, CellType::A)]);
+    state.regions[0].kernels = Some(
+        [ConstMatrix::new_with(7, 7, |(x, y)| {
+            if x.abs_diff(3) + y.abs_diff(3) == 1 {
+                1.0
+            } else {
+                0.0
+            }
+        }); 9],
+    );
+    let region_map = &mut state.region_maps[state.vec_ix];
+    for ixy in 0..region_map.height() {
+        for ixx in 50..region_map.width() {
+            region_map.set_at_index((ixx, ixy), 1);
+        }
+    }
+    // a blinker in region 0, a single cell in region 1
+    for (x, y) in [(10, 10), (11, 10), (12, 10), (70, 20)] {
+        state.set_at_index(x, y, &CellType::A);
+    }
+    assert_steps_match_reference(&mut state, 1);
+    let (left, right): (Vec<_>, Vec<_>) = cells_of(&state, CellType::A)
+        .into_iter()
+        .partition(|(x, _)| *x < 50);
+    assert_eq!(left, [(11, 9), (11, 10), (11, 11)]);
+    assert_eq!(right, [(70, 19), (69, 20), (70, 20), (71, 20), (70, 21)]);
+}
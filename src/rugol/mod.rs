@@ -1,11 +1,27 @@
 use crate::{
+    activity_map::ActivityMap,
+    age::CellAge,
     app_config::AppConfig,
+    breakpoint::Breakpoints,
     cell_type::{CellType, CellTypeMap},
     color::Color,
+    continuous::ContinuousParams,
+    elementary::OneDim,
     fade::Fader,
-    quad_tree::{Node, QuadTree},
+    fire_counts::FireCounts,
+    index_set::IndexSet,
+    interaction::Interaction,
+    margolus::Margolus,
+    mask::Mask,
+    reaction_diffusion::ReactionDiffusion,
+    region::Region,
     rules::RuleSet,
     save_file::SaveFile,
+    script::Script,
+    second_order::SecondOrder,
+    self_check::SelfCheck,
+    timeline::Timeline,
+    update_rule::RuleRegistry,
     FieldType,
 };
 use egui::TextureHandle;
@@ -13,8 +29,11 @@ use egui_dock::Tree;
 use instant::Instant;
 use matrices::{traits::Matrix, vec_matrix::VecMatrix};
 
+pub mod acc_histogram;
 pub mod conv_tabs;
+pub mod inspector;
 pub mod main;
+pub mod transition_graph;
 pub mod ui;
 
 /// <Field type matrix, Convolution matrix, Color matrix>
@@ -27,12 +46,35 @@ pub struct RugolState<
     conv_kernels: [C; 9],
     pub cell_type_map: CellTypeMap,
     rules: RuleSet<FieldType>,
+    pub continuous: ContinuousParams,
+    pub reaction_diffusion: ReactionDiffusion<CW>,
+    pub one_dim: OneDim,
+    pub margolus: Margolus,
+    pub second_order: SecondOrder,
+    pub age: CellAge,
+    pub timeline: Timeline,
+    pub script: Script,
+    /// rules written in Rust which can replace `rules`
+    pub rule_registry: RuleRegistry,
+    pub fire_counts: FireCounts,
+    /// conditions which pause the simulation
+    pub breakpoints: Breakpoints,
+    /// differences between the incremental and the full board step
+    pub self_check: SelfCheck,
     /// Vec of matrices with `FieldType` elements
     fields_vec: Vec<M>,
     /// Vec of matrices with `CellType` elements
     cell_type_vec: Vec<VecMatrix<CellType>>,
     /// Accumulator matrices with `FieldType` elements
     acc_vec: Vec<VecMatrix<FieldType>>,
+    /// Vec of matrices which mark the cells that are not changed by `step`
+    masks: Vec<VecMatrix<Mask>>,
+    /// Vec of matrices with the index of the region of every cell
+    region_maps: Vec<VecMatrix<u8>>,
+    /// rules and kernels of the regions 1.., region 0 uses `rules` and `conv_kernels`
+    regions: Vec<Region<C>>,
+    /// weights or kernels for every pair of centre and neighbour type, used by all regions
+    interaction: Option<Interaction<FieldType, C>>,
     /// Index to `fields_vec` and `cell_type_vec`
     vec_ix: usize,
     /// Index of the field the mouse is currently over
@@ -40,10 +82,17 @@ pub struct RugolState<
     pub hover_ix: Option<(usize, usize)>,
     pub fader: Fader<N>,
     pub config: AppConfig,
-    quad_tree: QuadTree<Node>,
+    activity: ActivityMap,
+    /// cells which are evaluated again in the next step,
+    /// they have a pending probabilistic rule or were skipped by the update schedule
+    pending: IndexSet,
+    /// number of steps taken
+    pub generation: usize,
     pub inst: Instant,
     pub frame_time: f64,
     pub save_file: Option<SaveFile<CW>>,
+    /// why the last file could not be opened
+    pub load_error: Option<String>,
     pub ui_down_arrow: Option<TextureHandle>,
     pub ui_up_arrow: Option<TextureHandle>,
     tree: Tree<usize>,
@@ -0,0 +1,112 @@
+use crate::{
+    cell_type::{CellType, CellTypeMap},
+    rules::{Rule, RuleSet},
+    FieldType,
+};
+use egui::{Align2, Color32, FontId, Painter, Pos2, Sense, Stroke, Ui, Vec2};
+use std::f32::consts::TAU;
+
+const NODE_RADIUS: f32 = 16.0;
+const FIRED: Color32 = Color32::from_rgb(255, 160, 0);
+
+/// Draws the rules as a directed graph, nodes are the cell types
+/// edges which fired in the last generation get thicker with the number of fires
+pub fn transition_graph_ui(
+    ui: &mut Ui,
+    rules: &RuleSet<FieldType>,
+    cell_type_map: &CellTypeMap,
+    fire_counts: &[usize],
+) {
+    let size = Vec2::splat(ui.available_width().clamp(300.0, 600.0));
+    let (response, painter) = ui.allocate_painter(size, Sense::hover());
+    let center = response.rect.center();
+    let radius = 0.5 * size.x - 3.0 * NODE_RADIUS;
+    let node_pos = |cell: CellType| {
+        let angle = TAU * cell.as_index() as f32 / 9.0 - 0.25 * TAU;
+        center + radius * Vec2::angled(angle)
+    };
+    let max_count = fire_counts.iter().copied().max().unwrap_or(0).max(1);
+    let text_color = ui.visuals().text_color();
+    // edges between the same pair of cell types are drawn side by side
+    let mut num_edges = [[0_usize; 9]; 9];
+    for (ix, rule) in rules.rules.iter().enumerate() {
+        let count = fire_counts.get(ix).copied().unwrap_or(0);
+        let stroke = if count > 0 {
+            Stroke::new(1.0 + 4.0 * count as f32 / max_count as f32, FIRED)
+        } else {
+            Stroke::new(1.0, Color32::GRAY)
+        };
+        let targets: Vec<(CellType, Option<f32>)> = if rule.weighted_transitions.is_empty() {
+            vec![(rule.transition, None)]
+        } else {
+            rule.weighted_transitions
+                .iter()
+                .map(|(cell, weight)| (*cell, Some(*weight)))
+                .collect()
+        };
+        for (transition, weight) in targets {
+            let edge_ix = &mut num_edges[rule.state.as_index()][transition.as_index()];
+            let offset = *edge_ix as f32;
+            *edge_ix += 1;
+            let label = edge_label(rule, weight, count);
+            let (from, to) = (node_pos(rule.state), node_pos(transition));
+            let label_pos = if rule.state == transition {
+                let outward = (from - center).normalized();
+                let loop_center = from + outward * (NODE_RADIUS + 10.0 + 12.0 * offset);
+                painter.circle_stroke(loop_center, 10.0 + 6.0 * offset, stroke);
+                loop_center + outward * (18.0 + 6.0 * offset)
+            } else {
+                let dir = (to - from).normalized();
+                let normal = dir.rot90() * (6.0 + 12.0 * offset);
+                let start = from + dir * NODE_RADIUS + normal;
+                let end = to - dir * NODE_RADIUS + normal;
+                painter.arrow(start, end - start, stroke);
+                start + 0.5 * (end - start) + 0.5 * normal
+            };
+            painter.text(
+                label_pos,
+                Align2::CENTER_CENTER,
+                label,
+                FontId::proportional(11.0),
+                if count > 0 { FIRED } else { text_color },
+            );
+        }
+    }
+    for ix in 0..9 {
+        let cell: CellType = ix.try_into().unwrap();
+        draw_node(&painter, node_pos(cell), cell, cell_type_map);
+    }
+}
+
+fn draw_node(painter: &Painter, pos: Pos2, cell: CellType, cell_type_map: &CellTypeMap) {
+    let [r, g, b, _]: [u8; 4] = cell_type_map[cell].0.into();
+    let fill = Color32::from_rgb(r, g, b);
+    let text = if (r as u32 + g as u32 + b as u32) < 384 {
+        Color32::WHITE
+    } else {
+        Color32::BLACK
+    };
+    painter.circle_filled(pos, NODE_RADIUS, fill);
+    painter.text(
+        pos,
+        Align2::CENTER_CENTER,
+        cell.to_string(),
+        FontId::proportional(12.0),
+        text,
+    );
+}
+
+/// range, probability, weight of the target and the number of fires in the last generation
+fn edge_label(rule: &Rule<FieldType>, weight: Option<f32>, count: usize) -> String {
+    let mut res = format!("{}..={}", rule.range.start(), rule.range.end());
+    if let Some(probability) = rule.transition_probability {
+        res += &format!(" p={probability}");
+    }
+    if let Some(weight) = weight {
+        res += &format!(" w={weight}");
+    }
+    if count > 0 {
+        res += &format!(" ×{count}");
+    }
+    res
+}
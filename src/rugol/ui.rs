@@ -1,17 +1,30 @@
-use super::conv_tabs::ConvWrapper;
+use super::{
+    acc_histogram::acc_histogram_ui, conv_tabs::ConvWrapper, transition_graph::transition_graph_ui,
+};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::save_file::*;
 use crate::{
+    app_config::{SimMode, UpdateSchedule},
+    breakpoint::Condition,
     cell_type::{CellType, CellTypeMap},
-    fade::Fader,
-    quad_tree::QuadTree,
+    color::ColorRamp,
+    continuous::GrowthFunction,
+    elementary::{OneDimRuleKind, MAX_EDITABLE_TABLE_LEN},
+    histogram::Histogram,
+    interaction::Interaction,
+    margolus::{Block, BlockRule, BlockRules},
+    mask::Mask,
+    reaction_diffusion::{laplacian_kernel, ReactionParams, ReactionTerm},
+    rule_lint::{lint_rules, KernelInfo, Lint},
     rules::{flame_rules, Rule},
-    RState, UiMode, CELLS, WARN_TEXT,
+    timeline::{Keyframe, TimelineEvent, ValueTrack},
+    topology::Topology,
+    FieldType, RState, UiMode, CELLS, WARN_TEXT,
 };
 use egui::emath::Numeric;
 use egui::*;
 use egui_dock::DockArea;
-use matrices::traits::Symmetry;
+use matrices::traits::{Matrix, Symmetry};
 use num_traits::{AsPrimitive, One, Zero};
 #[cfg(not(target_arch = "wasm32"))]
 use rfd::{AsyncFileDialog, FileDialog};
@@ -61,11 +74,55 @@ impl<const CW: usize> RState<CW> {
                         self.fields_vec[self.vec_ix].display_element(ix)
                     ));
                 }
-                ui.checkbox(&mut self.config.bdebug_quad_tree, "debug quad_tree");
-                if self.config.bdebug_quad_tree {
-                    ui.label(self.quad_tree.debug_levels().unwrap());
+                self.config.bupdate |= ui
+                    .checkbox(&mut self.config.bself_check, "self-check")
+                    .on_hover_text("compare every discrete step with a step of the whole board")
+                    .changed();
+                if self.config.bself_check {
+                    let self_check = &self.self_check;
+                    if let Some(reason) = self_check.skipped {
+                        ui.label(format!("not checked: {reason}"));
+                    }
+                    ui.label(format!(
+                        "{} generations checked, {} cells differ in the last one",
+                        self_check.generations_checked,
+                        self_check.mismatches.len()
+                    ));
+                    if let Some(generation) = self_check.last_mismatch {
+                        ui.colored_label(
+                            Color32::RED,
+                            format!("last difference in generation {generation}"),
+                        );
+                    }
+                }
+                ui.checkbox(&mut self.config.bdebug_activity, "debug activity map");
+                if self.config.bdebug_activity {
+                    ui.label(format!(
+                        "{} active tiles of {}x{} cells",
+                        self.activity.num_active(),
+                        self.activity.tile_size(),
+                        self.activity.tile_size()
+                    ));
+                    ui.label(RichText::new(self.activity.debug_tiles().unwrap()).monospace());
                 }
             });
+        let region = self.config.selected_region;
+        let rules = region
+            .checked_sub(1)
+            .and_then(|ix| self.regions.get(ix))
+            .map_or(&self.rules, |region| &region.rules);
+        Window::new(format!("transition graph of region {region}"))
+            .open(&mut self.config.bshow_transition_graph)
+            .show(ctx, |ui| {
+                transition_graph_ui(
+                    ui,
+                    rules,
+                    &self.cell_type_map,
+                    self.fire_counts.last(region),
+                );
+            });
+        self.histogram_window(ctx);
+        self.inspector_window(ctx);
     }
 
     fn main_ui(&mut self, ui: &mut Ui) {
@@ -90,6 +147,15 @@ impl<const CW: usize> RState<CW> {
                 self.value_changed_for(ct);
             }
         }
+        if self.config.ui_sections.show_timeline() {
+            self.timeline_ui(ui);
+        }
+        if self.config.ui_sections.show_script() {
+            self.script_ui(ui);
+        }
+        if self.config.ui_sections.show_breakpoints() {
+            self.breakpoints_ui(ui);
+        }
         if ui.button("Help").clicked() {
             self.config.mode = UiMode::Help;
         }
@@ -97,14 +163,27 @@ impl<const CW: usize> RState<CW> {
         if ui.button("Open file").clicked() {
             let file_dialog = AsyncFileDialog::new().pick_file();
             self.config.mode = UiMode::OpenFile;
-            self.save_file = pollster::block_on(async {
+            let loaded = pollster::block_on(async {
                 if let Some(file) = file_dialog.await {
                     let bytes = file.read().await;
-                    SaveFile::<CW>::load_from_bytes(&bytes).ok()
+                    Some(SaveFile::<CW>::load_from_bytes(&bytes))
                 } else {
                     None
                 }
             });
+            self.load_error = None;
+            self.save_file = match loaded {
+                Some(Ok(save_file)) => Some(save_file),
+                Some(Err(err)) => {
+                    self.load_error = Some(format!("the file could not be opened: {err}"));
+                    None
+                }
+                None => None,
+            };
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(err) = &self.load_error {
+            ui.colored_label(Color32::RED, err);
         }
         #[cfg(not(target_arch = "wasm32"))]
         if ui.button("Save file").clicked() {
@@ -156,6 +235,24 @@ impl<const CW: usize> RState<CW> {
                 &mut sections.hover_edit_colors,
                 "Edit colors and values",
             );
+            Self::select_section_and_hover_ui(
+                ui,
+                &mut sections.timeline,
+                &mut sections.hover_timeline,
+                "Timeline",
+            );
+            Self::select_section_and_hover_ui(
+                ui,
+                &mut sections.script,
+                &mut sections.hover_script,
+                "Script",
+            );
+            Self::select_section_and_hover_ui(
+                ui,
+                &mut sections.breakpoints,
+                &mut sections.hover_breakpoints,
+                "Breakpoints",
+            );
         });
     }
 
@@ -198,21 +295,243 @@ impl<const CW: usize> RState<CW> {
             }
             ui.label("clear value:");
             Self::edit_cell_type(ui, &mut self.config.clear_val);
+            ui.checkbox(&mut self.config.bclear_mask, "clear mask");
         });
     }
 
     fn control_ui(&mut self, ui: &mut Ui) {
-        if self.config.paused {
-            ui.horizontal(|ui| {
+        ui.horizontal(|ui| {
+            if self.config.paused {
                 if ui.button("▶").clicked() {
                     self.config.paused = false;
                 }
                 if ui.button("›").clicked() {
                     self.step();
                 }
+            } else if ui.button("⏸").clicked() {
+                self.config.paused = true;
+            }
+            ui.label(format!("generation: {}", self.generation));
+        });
+    }
+
+    fn script_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .checkbox(&mut self.config.bscript_rules, "use the rule script")
+                .changed()
+            {
+                self.everything_changed();
+            }
+            if self.config.sim_mode != SimMode::Discrete {
+                ui.label("(discrete mode only)");
+            }
+        });
+        ui.label("rule script:");
+        if ui
+            .add(TextEdit::multiline(&mut self.script.sources.rule).code_editor())
+            .changed()
+        {
+            self.script.compile_rule();
+            self.everything_changed();
+        }
+        ui.label("automation script:");
+        ui.add(TextEdit::multiline(&mut self.script.sources.automation).code_editor());
+        ui.horizontal(|ui| {
+            if ui.button("run automation script").clicked() {
+                self.run_automation_script();
+            }
+            if !self.script.queue.is_empty() {
+                ui.label(format!("{} commands left", self.script.queue.len()));
+                if ui.button("stop").clicked() {
+                    self.script.queue.clear();
+                }
+            }
+        });
+        if let Some(err) = &self.script.error {
+            ui.colored_label(Color32::RED, err);
+        }
+    }
+
+    fn breakpoints_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("add breakpoint:");
+            let condition = if ui.button("cell changes").clicked() {
+                Some(Condition::CellChanges(
+                    self.config.inspect_ix.unwrap_or((0, 0)),
+                ))
+            } else if ui.button("population crosses").clicked() {
+                Some(Condition::PopulationCrosses(CellType::A, 100))
+            } else if ui.button("no change").clicked() {
+                Some(Condition::NoChange)
+            } else if ui.button("rule fires").clicked() {
+                Some(Condition::RuleFires {
+                    region: self.config.selected_region,
+                    rule: 0,
+                })
+            } else if ui.button("generation").clicked() {
+                Some(Condition::Generation(self.generation + 1000))
+            } else {
+                None
+            };
+            if let Some(condition) = condition {
+                self.breakpoints.add(condition);
+            }
+        });
+        if let Some((ix, generation)) = self.breakpoints.last_hit {
+            if let Some(breakpoint) = self.breakpoints.list.get(ix) {
+                ui.label(format!(
+                    "paused in generation {generation}: {}",
+                    breakpoint.condition
+                ));
+            }
+        }
+        let mut remove = None;
+        for (ix, breakpoint) in self.breakpoints.list.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut breakpoint.benabled, "");
+                match &mut breakpoint.condition {
+                    Condition::CellChanges((x, y)) => {
+                        ui.label("the cell x:");
+                        ui.add(DragValue::new(x));
+                        ui.label("y:");
+                        ui.add(DragValue::new(y));
+                        ui.label("changes");
+                    }
+                    Condition::PopulationCrosses(cell_type, threshold) => {
+                        ui.label("the population of");
+                        Self::edit_cell_type(ui, cell_type);
+                        ui.label("crosses");
+                        ui.add(DragValue::new(threshold));
+                    }
+                    Condition::NoChange => {
+                        ui.label("no cell changed");
+                    }
+                    Condition::RuleFires { region, rule } => {
+                        ui.label("rule");
+                        ui.add(DragValue::new(rule));
+                        ui.label("of region");
+                        ui.add(DragValue::new(region));
+                        ui.label("fires");
+                    }
+                    Condition::Generation(generation) => {
+                        ui.label("generation");
+                        ui.add(DragValue::new(generation));
+                    }
+                }
+                if ui.button("remove").clicked() {
+                    remove = Some(ix);
+                }
             });
-        } else if ui.button("⏸").clicked() {
-            self.config.paused = true;
+        }
+        if let Some(ix) = remove {
+            self.breakpoints.list.remove(ix);
+            self.breakpoints.last_hit = None;
+        }
+    }
+
+    fn timeline_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("reset generation").clicked() {
+                self.reset_generation();
+            }
+            if ui.button("clear timeline").clicked() {
+                self.timeline = Default::default();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("add keyframe:");
+            let event = if ui.button("switch to current rules").clicked() {
+                Some(TimelineEvent::SetRules(self.rules.clone()))
+            } else if ui.button("stamp").clicked() {
+                Some(TimelineEvent::Stamp {
+                    position: self.config.stamp_origin,
+                    cells: self.copy_cells(self.config.stamp_origin, self.config.stamp_size),
+                })
+            } else if ui.button("pause").clicked() {
+                Some(TimelineEvent::Pause)
+            } else {
+                None
+            };
+            if let Some(event) = event {
+                self.timeline.keyframes.push(Keyframe {
+                    generation: self.generation + 1,
+                    event,
+                });
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("stamp from x:");
+            ui.add(DragValue::new(&mut self.config.stamp_origin.0));
+            ui.label("y:");
+            ui.add(DragValue::new(&mut self.config.stamp_origin.1));
+            ui.label("width:");
+            ui.add(DragValue::new(&mut self.config.stamp_size.0).clamp_range(1..=800));
+            ui.label("height:");
+            ui.add(DragValue::new(&mut self.config.stamp_size.1).clamp_range(1..=400));
+        });
+        let mut remove = None;
+        for (ix, keyframe) in self.timeline.keyframes.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label("generation:");
+                ui.add(DragValue::new(&mut keyframe.generation));
+                match &mut keyframe.event {
+                    TimelineEvent::SetRules(rules) => {
+                        ui.label(format!("switch to {} rules", rules.rules.len()));
+                        if ui.button("replace with current rules").clicked() {
+                            *rules = self.rules.clone();
+                        }
+                    }
+                    TimelineEvent::Stamp { position, cells } => {
+                        ui.label(format!("stamp {}x{} at x:", cells.width(), cells.height()));
+                        ui.add(DragValue::new(&mut position.0));
+                        ui.label("y:");
+                        ui.add(DragValue::new(&mut position.1));
+                    }
+                    TimelineEvent::Pause => {
+                        ui.label("pause");
+                    }
+                }
+                if ui.button("remove").clicked() {
+                    remove = Some(ix);
+                }
+            });
+        }
+        if let Some(ix) = remove {
+            self.timeline.keyframes.remove(ix);
+        }
+        ui.separator();
+        if ui.button("add value ramp").clicked() {
+            let cell_type = self.cell_type_map.get_selected_rules_cell();
+            let value = self.cell_type_map[cell_type].1;
+            self.timeline.tracks.push(ValueTrack {
+                cell_type,
+                start: self.generation + 1,
+                end: self.generation + 1000,
+                from: value,
+                to: value,
+            });
+        }
+        let mut remove = None;
+        for (ix, track) in self.timeline.tracks.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label("value of");
+                Self::edit_cell_type(ui, &mut track.cell_type);
+                ui.label("from");
+                ui.add(DragValue::new(&mut track.from).speed(0.01));
+                ui.label("to");
+                ui.add(DragValue::new(&mut track.to).speed(0.01));
+                ui.label("in generations");
+                ui.add(DragValue::new(&mut track.start));
+                ui.label("..=");
+                ui.add(DragValue::new(&mut track.end).clamp_range(track.start..=usize::MAX));
+                if ui.button("remove").clicked() {
+                    remove = Some(ix);
+                }
+            });
+        }
+        if let Some(ix) = remove {
+            self.timeline.tracks.remove(ix);
         }
     }
 
@@ -222,18 +541,68 @@ impl<const CW: usize> RState<CW> {
                 .radio_value(&mut self.vec_ix, ix, format!("{}x{}", w, h))
                 .changed()
             {
-                self.fader = Fader::new(*w, *h);
-                self.quad_tree = QuadTree::new(*w, *h, 5);
-                self.config.bnew_size = true;
-                self.config.bupdate = true;
+                self.board_size_changed();
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("simulation mode:");
+            for (sim_mode, name) in [
+                (SimMode::Discrete, "discrete"),
+                (SimMode::Continuous, "continuous"),
+                (SimMode::ReactionDiffusion, "reaction diffusion"),
+                (SimMode::OneDimensional, "1D"),
+                (SimMode::Margolus, "Margolus"),
+                (SimMode::SecondOrder, "second order"),
+            ] {
+                if ui.radio(self.config.sim_mode == sim_mode, name).clicked() {
+                    self.set_sim_mode(sim_mode);
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("topology:");
+            for (topology, name) in [
+                (Topology::Square, "square"),
+                (Topology::Hexagonal, "hexagonal"),
+                (Topology::Triangular, "triangular"),
+            ] {
+                if ui.radio(self.config.topology == topology, name).clicked() {
+                    self.set_topology(topology);
+                }
+            }
+        });
+        self.mask_ui(ui);
+        match self.config.sim_mode {
+            SimMode::Discrete => {
+                self.schedule_ui(ui);
+                self.age_ui(ui);
+            }
+            SimMode::Continuous => self.continuous_ui(ui),
+            SimMode::ReactionDiffusion => self.reaction_diffusion_ui(ui),
+            SimMode::OneDimensional => self.one_dim_ui(ui),
+            SimMode::Margolus => self.margolus_ui(ui),
+            SimMode::SecondOrder => {
+                self.second_order_ui(ui);
+                self.age_ui(ui);
             }
         }
         ui.checkbox(&mut self.config.bsingle_kernel, "single kernel");
         ui.checkbox(&mut self.config.bmirror_conv_kernels, "mirror kernels");
-        ui.checkbox(&mut self.config.brandom_rules, "random rules");
+        if ui
+            .checkbox(&mut self.config.brandom_rules, "random rules")
+            .changed()
+        {
+            // cells with pending probabilistic rules are only known after a full evaluation
+            self.everything_changed();
+        }
         ui.checkbox(&mut self.config.bfade, "fade");
         ui.add(Slider::new(&mut self.fader.mix_factor, 0.0_f32..=1.0).text("Fader: mix_factor"));
-        ui.checkbox(&mut self.config.bdebug_window, "debug window");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.config.bdebug_window, "debug window");
+            self.config.bupdate |= ui
+                .checkbox(&mut self.config.binspector, "inspector")
+                .changed();
+        });
         ui.checkbox(&mut self.config.sym_editting, "symmetric editting");
         if self.config.sym_editting {
             self.edit_symmetry(ui);
@@ -255,15 +624,570 @@ impl<const CW: usize> RState<CW> {
         );
     }
 
+    fn continuous_ui(&mut self, ui: &mut Ui) {
+        let params = &mut self.continuous;
+        ui.horizontal(|ui| {
+            ui.label("growth function:");
+            for (growth, name) in [
+                (GrowthFunction::Gaussian, "gaussian"),
+                (GrowthFunction::Step, "step"),
+                (GrowthFunction::Polynomial, "polynomial"),
+            ] {
+                ui.radio_value(&mut params.growth, growth, name);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("mu:");
+            ui.add(DragValue::new(&mut params.mu).speed(0.001));
+            ui.label("sigma:");
+            ui.add(
+                DragValue::new(&mut params.sigma)
+                    .speed(0.001)
+                    .clamp_range(0.0001..=FieldType::MAX),
+            );
+            ui.label("dt:");
+            ui.add(
+                DragValue::new(&mut params.dt)
+                    .speed(0.001)
+                    .clamp_range(0.0..=1.0),
+            );
+        });
+        if Self::color_ramp_ui(ui, &mut params.color_ramp) {
+            self.config.bupdate = true;
+        }
+        let generator = &mut self.config.kernel_generator;
+        ui.horizontal(|ui| {
+            if ui.button("ring kernel").clicked() {
+                generator.ring(&mut self.conv_kernels[0]);
+            }
+            ui.label("center:");
+            ui.add(
+                DragValue::new(&mut generator.ring_center)
+                    .speed(0.01)
+                    .clamp_range(0.0..=1.0),
+            );
+            ui.label("width:");
+            ui.add(
+                DragValue::new(&mut generator.ring_width)
+                    .speed(0.01)
+                    .clamp_range(0.01..=1.0),
+            );
+        });
+        ui.horizontal(|ui| {
+            if ui.button("gaussian kernel").clicked() {
+                generator.gaussian(&mut self.conv_kernels[0]);
+            }
+            ui.label("sigma:");
+            ui.add(
+                DragValue::new(&mut generator.gauss_sigma)
+                    .speed(0.01)
+                    .clamp_range(0.01..=2.0),
+            );
+        });
+    }
+
+    fn reaction_diffusion_ui(&mut self, ui: &mut Ui) {
+        let rd = &mut self.reaction_diffusion;
+        let mut breseed = false;
+        ui.horizontal(|ui| {
+            ui.label("presets:");
+            for (name, params) in [
+                (
+                    "Gray-Scott",
+                    ReactionParams::gray_scott as fn() -> ReactionParams,
+                ),
+                ("FitzHugh-Nagumo", ReactionParams::fitzhugh_nagumo),
+            ] {
+                if ui.button(name).clicked() {
+                    rd.params = params();
+                    for kernel in rd.kernels.iter_mut() {
+                        laplacian_kernel(kernel);
+                    }
+                    breseed = true;
+                }
+            }
+            if ui.button("seed").clicked() {
+                breseed = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("feed:");
+            ui.add(DragValue::new(&mut rd.params.feed).speed(0.0001));
+            ui.label("kill:");
+            ui.add(DragValue::new(&mut rd.params.kill).speed(0.0001));
+            ui.label("dt:");
+            ui.add(
+                DragValue::new(&mut rd.params.dt)
+                    .speed(0.001)
+                    .clamp_range(0.0..=10.0),
+            );
+        });
+        let num_fields = rd.params.fields.len();
+        let names: Vec<String> = rd.params.fields.iter().map(|f| f.name.clone()).collect();
+        let mut o_delete_ix = None;
+        for (field_ix, field) in rd.params.fields.iter_mut().enumerate() {
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(&mut field.name).desired_width(30.0));
+                ui.label("diffusion:");
+                ui.add(DragValue::new(&mut field.diffusion).speed(0.01));
+                let mut edit_color_u8: [u8; 4] = field.color.into();
+                if ui
+                    .color_edit_button_srgba_unmultiplied(&mut edit_color_u8)
+                    .changed()
+                {
+                    field.color = edit_color_u8.into();
+                    self.config.bupdate = true;
+                }
+                ui.label("initial:");
+                ui.add(DragValue::new(&mut field.initial).speed(0.01));
+                ui.label("seed:");
+                ui.add(DragValue::new(&mut field.seed).speed(0.01));
+                if ui.button("laplacian kernel").clicked() {
+                    laplacian_kernel(&mut rd.kernels[field_ix]);
+                }
+                if num_fields > 2 && ui.button("Delete field").clicked() {
+                    o_delete_ix = Some(field_ix);
+                }
+            });
+            let mut o_delete_term_ix = None;
+            for (term_ix, term) in field.reaction.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut term.coefficient).speed(0.01));
+                    ui.label("+ feed *");
+                    ui.add(DragValue::new(&mut term.feed_factor).speed(0.01));
+                    ui.label("+ kill *");
+                    ui.add(DragValue::new(&mut term.kill_factor).speed(0.01));
+                    for (name, power) in names.iter().zip(term.powers.iter_mut()) {
+                        ui.label(format!("* {name}^"));
+                        ui.add(DragValue::new(power).clamp_range(0..=9));
+                    }
+                    if ui.small_button("x").clicked() {
+                        o_delete_term_ix = Some(term_ix);
+                    }
+                });
+            }
+            if let Some(delete_ix) = o_delete_term_ix {
+                field.reaction.remove(delete_ix);
+            }
+            if ui.button("Add term").clicked() {
+                field
+                    .reaction
+                    .push(ReactionTerm::new(0.0, vec![0; num_fields]));
+            }
+        }
+        if let Some(delete_ix) = o_delete_ix {
+            rd.params.fields.remove(delete_ix);
+            for field in rd.params.fields.iter_mut() {
+                for term in field.reaction.iter_mut() {
+                    term.powers.remove(delete_ix);
+                }
+            }
+            breseed = true;
+        }
+        if num_fields < rd.kernels.len() && ui.button("Add field").clicked() {
+            let mut field = rd.params.fields[num_fields - 1].clone();
+            field.name = format!("f{num_fields}");
+            field.reaction.clear();
+            for field in rd.params.fields.iter_mut() {
+                for term in field.reaction.iter_mut() {
+                    term.powers.push(0);
+                }
+            }
+            rd.params.fields.push(field);
+            laplacian_kernel(&mut rd.kernels[num_fields]);
+            breseed = true;
+        }
+        if breseed {
+            rd.seed();
+            self.config.bupdate = true;
+        }
+    }
+
+    fn one_dim_ui(&mut self, ui: &mut Ui) {
+        let rule = &mut self.one_dim.rule;
+        ui.horizontal(|ui| {
+            ui.label("rule:");
+            ui.radio_value(&mut rule.kind, OneDimRuleKind::Wolfram, "Wolfram");
+            ui.radio_value(&mut rule.kind, OneDimRuleKind::Totalistic, "totalistic");
+            ui.radio_value(&mut rule.kind, OneDimRuleKind::General, "general");
+        });
+        match rule.kind {
+            OneDimRuleKind::Wolfram => {
+                ui.horizontal(|ui| {
+                    ui.label("rule number:");
+                    ui.add(DragValue::new(&mut rule.wolfram));
+                    for number in [30, 90, 110, 184] {
+                        if ui.button(format!("{number}")).clicked() {
+                            rule.wolfram = number;
+                        }
+                    }
+                });
+            }
+            OneDimRuleKind::Totalistic | OneDimRuleKind::General => {
+                ui.horizontal(|ui| {
+                    let mut changed = false;
+                    ui.label("states:");
+                    changed |= ui
+                        .add(DragValue::new(&mut rule.states).clamp_range(2..=9))
+                        .changed();
+                    ui.label("radius:");
+                    changed |= ui
+                        .add(DragValue::new(&mut rule.radius).clamp_range(1..=3))
+                        .changed();
+                    if changed {
+                        rule.resize_tables();
+                    }
+                });
+                let table = if rule.kind == OneDimRuleKind::Totalistic {
+                    ui.label("new state for the sum of the neighbourhood:");
+                    &mut rule.totalistic
+                } else {
+                    if ui.button("random table").clicked() {
+                        rule.randomize_table();
+                    }
+                    ui.label("new state for every neighbourhood:");
+                    &mut rule.table
+                };
+                if table.len() <= MAX_EDITABLE_TABLE_LEN {
+                    for (chunk_ix, chunk) in table.chunks_mut(9).enumerate() {
+                        ui.horizontal(|ui| {
+                            for (ix, cell) in chunk.iter_mut().enumerate() {
+                                ui.label(format!("{}:", chunk_ix * 9 + ix));
+                                Self::edit_cell_type(ui, cell);
+                            }
+                        });
+                    }
+                } else {
+                    ui.label(format!("{} entries are too many to edit", table.len()));
+                }
+            }
+        }
+        ui.horizontal(|ui| {
+            if ui.button("single seed").clicked() {
+                self.one_dim
+                    .seed_single(&mut self.cell_type_vec[self.vec_ix]);
+                self.config.bupdate = true;
+            }
+            if ui.button("random seed").clicked() {
+                self.one_dim
+                    .seed_random(&mut self.cell_type_vec[self.vec_ix]);
+                self.config.bupdate = true;
+            }
+        });
+    }
+
+    fn margolus_ui(&mut self, ui: &mut Ui) {
+        let rules = &mut self.margolus.rules;
+        ui.horizontal(|ui| {
+            ui.label("presets:");
+            if ui.button("billiard ball").clicked() {
+                *rules = BlockRules::billiard_ball();
+            }
+            if ui.button("gas").clicked() {
+                *rules = BlockRules::gas();
+            }
+            if ui.button("sand").clicked() {
+                *rules = BlockRules::sand();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut rules.brotations, "match rotations");
+            if ui.button("expand rotations").clicked() {
+                rules.expand_rotations();
+            }
+            if ui.button("add entry").clicked() {
+                rules
+                    .table
+                    .push(BlockRule::new([CellType::NoCell; 4], [CellType::NoCell; 4]));
+            }
+        });
+        let mut remove = None;
+        for (ix, rule) in rules.table.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                Self::edit_block(ui, &mut rule.from);
+                ui.label("→");
+                Self::edit_block(ui, &mut rule.to);
+                if ui.button("⟳").on_hover_text("rotate by 90°").clicked() {
+                    *rule = rule.rotated();
+                }
+                if ui.button("x").clicked() {
+                    remove = Some(ix);
+                }
+            });
+        }
+        if let Some(ix) = remove {
+            rules.table.remove(ix);
+        }
+    }
+
+    fn mask_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("paint:");
+            for (paint_mask, name) in [
+                (None, "cells"),
+                (Some(Mask::Wall), "wall"),
+                (Some(Mask::Source(CellType::NoCell)), "source"),
+                (Some(Mask::Sink), "sink"),
+                (Some(Mask::Open), "erase mask"),
+            ] {
+                if ui
+                    .radio_value(&mut self.config.paint_mask, paint_mask, name)
+                    .clicked()
+                {
+                    self.config.paint_region = None;
+                }
+            }
+            self.config.bupdate |= ui
+                .checkbox(&mut self.config.bshow_mask, "show mask")
+                .changed();
+        });
+    }
+
+    fn color_ramp_ui(ui: &mut Ui, color_ramp: &mut ColorRamp) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("color ramp:");
+            for color in color_ramp.stops.iter_mut() {
+                let mut edit_color_u8: [u8; 4] = (*color).into();
+                if ui
+                    .color_edit_button_srgba_unmultiplied(&mut edit_color_u8)
+                    .changed()
+                {
+                    *color = edit_color_u8.into();
+                    changed = true;
+                }
+            }
+        });
+        changed
+    }
+
+    fn age_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .checkbox(&mut self.config.btrack_age, "track age")
+                .changed()
+            {
+                let (w, h) = CELLS[self.vec_ix];
+                self.age.reset(w, h, self.generation);
+                // cells with age rules have to be found again
+                self.everything_changed();
+            }
+            if self.config.btrack_age {
+                self.config.bupdate |= ui
+                    .checkbox(&mut self.config.bcolor_by_age, "color by age")
+                    .changed();
+            }
+        });
+        if self.config.btrack_age && self.config.bcolor_by_age {
+            ui.horizontal(|ui| {
+                ui.label("age of the last color:");
+                self.config.bupdate |= ui
+                    .add(DragValue::new(&mut self.age.max_color_age).clamp_range(1..=100000))
+                    .changed();
+            });
+            self.config.bupdate |= Self::color_ramp_ui(ui, &mut self.age.color_ramp);
+        }
+    }
+
+    fn schedule_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("update schedule:");
+            for (schedule, name) in [
+                (UpdateSchedule::Synchronous, "synchronous"),
+                (UpdateSchedule::RandomSequential, "random sequential"),
+                (UpdateSchedule::Checkerboard, "checkerboard"),
+                (UpdateSchedule::Alpha, "α"),
+            ] {
+                ui.radio_value(&mut self.config.schedule, schedule, name);
+            }
+        });
+        if self.config.schedule == UpdateSchedule::Alpha {
+            ui.add(
+                Slider::new(&mut self.config.update_probability, 0.0..=1.0)
+                    .text("update probability"),
+            );
+        }
+    }
+
+    fn second_order_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("states:");
+            ui.add(DragValue::new(&mut self.second_order.states).clamp_range(2..=9));
+        });
+        ui.label("next = (rules(current) - previous) mod states");
+        if ui.button("reverse time").clicked() {
+            self.reverse_time();
+        }
+    }
+
+    fn edit_block(ui: &mut Ui, block: &mut Block) {
+        ui.vertical(|ui| {
+            for row in block.chunks_mut(2) {
+                ui.horizontal(|ui| {
+                    for cell in row.iter_mut() {
+                        Self::edit_cell_type(ui, cell);
+                    }
+                });
+            }
+        });
+    }
+
     fn edit_rules_ui(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
-            if ui.add(Button::new("Add rule")).clicked() {
-                self.rules.rules.push(Rule {
-                    state: CellType::NoCell,
-                    range: Zero::zero()..=Zero::zero(),
-                    transition: CellType::NoCell,
-                    transition_probability: None,
+            ui.checkbox(&mut self.config.bshow_transition_graph, "transition graph");
+            ui.checkbox(&mut self.config.bshow_histogram, "histogram");
+        });
+        self.update_rule_ui(ui);
+        self.region_ui(ui);
+        // the rules of the selected region are edited in place of the rules of region 0
+        self.swap_selected_region_rules();
+        self.edit_rule_set_ui(ui);
+        self.swap_selected_region_rules();
+    }
+
+    /// histogram of the accumulated values of one cell type in the selected region
+    fn histogram_window(&mut self, ctx: &Context) {
+        let region = self.config.selected_region;
+        let state = self.config.histogram_cell_type;
+        let cell_types = &self.cell_type_vec[self.vec_ix];
+        let region_map = &self.region_maps[self.vec_ix];
+        let acc_matrix = &self.acc_vec[self.vec_ix];
+        let mut o_changed = None;
+        Window::new(format!("accumulated values in region {region}"))
+            .open(&mut self.config.bshow_histogram)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("cell type:");
+                    Self::edit_cell_type(ui, &mut self.config.histogram_cell_type);
+                    ui.label("bins:");
+                    ui.add(DragValue::new(&mut self.config.histogram_bins).clamp_range(1..=500));
                 });
+                let values: Vec<FieldType> = (0..cell_types.height())
+                    .flat_map(|y| (0..cell_types.width()).map(move |x| (x, y)))
+                    .filter(|ix| {
+                        cell_types.index(*ix) == state && region_map.index(*ix) as usize == region
+                    })
+                    .map(|ix| acc_matrix.index(ix))
+                    .collect();
+                let histogram = match self.config.histogram_drag_range.clone() {
+                    Some(range) => {
+                        Histogram::with_range(&values, self.config.histogram_bins, range)
+                    }
+                    None => Histogram::new(&values, self.config.histogram_bins),
+                };
+                let rules = match region
+                    .checked_sub(1)
+                    .and_then(|ix| self.regions.get_mut(ix))
+                {
+                    Some(region) => &mut region.rules,
+                    None => &mut self.rules,
+                };
+                let (bchanged, bdragging) =
+                    acc_histogram_ui(ui, &histogram, rules, state, &self.cell_type_map);
+                if bchanged {
+                    o_changed = Some(state);
+                }
+                self.config.histogram_drag_range = bdragging.then_some(histogram.range);
+            });
+        if let Some(state) = o_changed {
+            self.fire_counts.reset();
+            self.value_changed_for(state);
+        }
+    }
+
+    fn update_rule_ui(&mut self, ui: &mut Ui) {
+        if self.rule_registry.is_empty() {
+            return;
+        }
+        ui.horizontal_wrapped(|ui| {
+            ui.label("update rule:");
+            let mut update_rule = self.config.update_rule;
+            ui.radio_value(&mut update_rule, None, "rule set");
+            for (ix, name) in self.rule_registry.names().enumerate() {
+                ui.radio_value(&mut update_rule, Some(ix), name);
+            }
+            if update_rule != self.config.update_rule {
+                self.config.update_rule = update_rule;
+                self.everything_changed();
+            }
+        });
+    }
+
+    fn swap_selected_region_rules(&mut self) {
+        if let Some(region) = self
+            .config
+            .selected_region
+            .checked_sub(1)
+            .and_then(|ix| self.regions.get_mut(ix))
+        {
+            std::mem::swap(&mut self.rules, &mut region.rules);
+        }
+    }
+
+    fn region_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("region:");
+            for region in 0..self.num_regions() {
+                if ui
+                    .selectable_label(self.config.selected_region == region, region.to_string())
+                    .clicked()
+                {
+                    self.config.selected_region = region;
+                    if self.config.paint_region.is_some() {
+                        self.config.paint_region = Some(region as u8);
+                    }
+                }
+            }
+            if ui.button("add region").clicked() {
+                self.add_region();
+            }
+            if self.config.selected_region > 0 && ui.button("remove region").clicked() {
+                self.remove_region(self.config.selected_region);
+            }
+        });
+        ui.horizontal(|ui| {
+            let mut bpaint = self.config.paint_region.is_some();
+            if ui.checkbox(&mut bpaint, "paint region").changed() {
+                self.config.paint_region = bpaint.then_some(self.config.selected_region as u8);
+                if bpaint {
+                    self.config.paint_mask = None;
+                }
+            }
+            self.config.bupdate |= ui
+                .checkbox(&mut self.config.bshow_regions, "show regions")
+                .changed();
+            if let Some(region) = self
+                .config
+                .selected_region
+                .checked_sub(1)
+                .and_then(|ix| self.regions.get_mut(ix))
+            {
+                let mut bkernels = region.kernels.is_some();
+                if ui.checkbox(&mut bkernels, "own kernels").changed() {
+                    region.kernels = bkernels.then_some(self.conv_kernels);
+                    self.everything_changed();
+                }
+            }
+        });
+    }
+
+    fn edit_rule_set_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.add(Button::new("Add rule")).clicked() {
+                self.rules.rules.push(Rule::new(
+                    CellType::NoCell,
+                    Zero::zero()..=Zero::zero(),
+                    CellType::NoCell,
+                ));
+                self.fire_counts.reset();
+            }
+            if ui
+                .checkbox(&mut self.rules.bcompeting, "competing rules")
+                .changed()
+            {
+                self.fire_counts.reset();
+                self.everything_changed();
             }
             if CW == 5 && ui.button("Flame").clicked() {
                 self.conv_kernels[0].data = [[Zero::zero(); CW]; CW];
@@ -279,8 +1203,12 @@ impl<const CW: usize> RState<CW> {
                     }
                 }
                 self.rules = flame_rules();
+                self.fire_counts.reset();
             }
         });
+        let lints = self.lint_rules();
+        let region = self.config.selected_region;
+        let bcounted = self.fire_counts.generations() > 0;
         let mut o_delete_ix = None;
         let mut o_up_ix = None;
         let mut o_down_ix = None;
@@ -289,6 +1217,24 @@ impl<const CW: usize> RState<CW> {
         let num_rules = self.rules.rules.len();
         for (del_ix, rule) in self.rules.rules.iter_mut().enumerate() {
             ui.horizontal(|ui| {
+                let messages: Vec<&str> = lints
+                    .iter()
+                    .filter(|lint| lint.rule == del_ix)
+                    .map(|lint| lint.message.as_str())
+                    .collect();
+                if !messages.is_empty() {
+                    ui.colored_label(Color32::YELLOW, "⚠")
+                        .on_hover_text(messages.join("\n"));
+                }
+                if bcounted {
+                    let last = self.fire_counts.last(region).get(del_ix).copied();
+                    let total = self.fire_counts.total(region).get(del_ix).copied();
+                    ui.label(format!("×{} / {}", last.unwrap_or(0), total.unwrap_or(0)))
+                        .on_hover_text(
+                            "fires among the active cells in the last generation / since the counters were reset\n\
+                             cells with an unchanged neighbourhood are not evaluated and not counted",
+                        );
+                }
                 changed |= Self::edit_cell_type(ui, &mut rule.state);
                 ui.label("->");
                 changed |= Self::edit_cell_type(ui, &mut rule.transition);
@@ -297,6 +1243,20 @@ impl<const CW: usize> RState<CW> {
                     rule.range = range;
                     changed = true;
                 }
+                if self.config.btrack_age {
+                    let mut bage = rule.age_range.is_some();
+                    if ui.checkbox(&mut bage, "age").changed() {
+                        rule.age_range = bage.then_some(0..=u32::MAX);
+                        changed = true;
+                    }
+                    if let Some(age_range) = rule.age_range.as_mut() {
+                        let (mut start, mut end) = (*age_range.start(), *age_range.end());
+                        changed |= ui.add(DragValue::new(&mut start)).changed();
+                        ui.label("..=");
+                        changed |= ui.add(DragValue::new(&mut end)).changed();
+                        *age_range = start..=end;
+                    }
+                }
                 if ui.add(Button::new("Delete rule")).clicked() {
                     o_delete_ix = Some(del_ix);
                     changed = true;
@@ -304,6 +1264,7 @@ impl<const CW: usize> RState<CW> {
                 let mut btp = rule.transition_probability.is_some();
                 if self.config.brandom_rules {
                     if ui.checkbox(&mut btp, "random?").changed() {
+                        changed = true;
                         if btp {
                             rule.transition_probability = Some(1.0);
                         } else {
@@ -344,6 +1305,14 @@ impl<const CW: usize> RState<CW> {
                     changed_states.push(rule.state);
                 }
             });
+            if Self::edit_weighted_transitions_ui(ui, rule) {
+                changed_states.push(rule.state);
+                changed = true;
+            }
+        }
+        if changed || o_up_ix.is_some() || o_down_ix.is_some() {
+            // the counts belong to the old rules
+            self.fire_counts.reset();
         }
         if let Some(del_ix) = o_delete_ix {
             self.rules.rules.remove(del_ix);
@@ -364,6 +1333,85 @@ impl<const CW: usize> RState<CW> {
                 self.value_changed_for(cell_type);
             }
         }
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{:.0}% of the rules fired among the active cells in {} generations",
+                100.0 * self.fire_counts.coverage(region),
+                self.fire_counts.generations()
+            ));
+            if ui.button("reset counters").clicked() {
+                self.fire_counts.reset();
+            }
+        });
+        CollapsingHeader::new(format!("rule analysis ({} problems)", lints.len())).show(ui, |ui| {
+            for lint in lints.iter() {
+                ui.colored_label(
+                    Color32::YELLOW,
+                    format!("rule {}: {}", lint.rule, lint.message),
+                );
+            }
+        });
+    }
+
+    /// problems of the rules which are edited, checked against the kernels they are used with
+    fn lint_rules(&self) -> Vec<Lint> {
+        let region = self.config.selected_region;
+        let kernels = region
+            .checked_sub(1)
+            .and_then(|ix| self.regions.get(ix))
+            .and_then(|region| region.kernels.as_ref())
+            .unwrap_or(&self.conv_kernels);
+        let values: Vec<FieldType> = (0..9)
+            .map(|ix| self.cell_type_map[self.cell_type_map.inv_lookup(ix)].1)
+            .collect();
+        let kernel_info = KernelInfo::new(
+            kernels,
+            self.config.bsingle_kernel,
+            self.interaction.as_ref(),
+            &self.config.reductions,
+            &values,
+        );
+        lint_rules(&self.rules, &kernel_info)
+    }
+
+    /// returns true if the weighted transitions of `rule` changed
+    fn edit_weighted_transitions_ui(ui: &mut Ui, rule: &mut Rule<FieldType>) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            let mut bweighted = !rule.weighted_transitions.is_empty();
+            if ui.checkbox(&mut bweighted, "weighted?").changed() {
+                if bweighted {
+                    rule.weighted_transitions = vec![(rule.transition, 1.0)];
+                } else {
+                    rule.weighted_transitions.clear();
+                }
+                changed = true;
+            }
+            let mut o_delete_ix = None;
+            for (ix, (cell, weight)) in rule.weighted_transitions.iter_mut().enumerate() {
+                ui.add(Separator::default());
+                changed |= Self::edit_cell_type(ui, cell);
+                changed |= ui
+                    .add(
+                        DragValue::new(weight)
+                            .speed(0.01)
+                            .clamp_range(0.0..=f32::MAX),
+                    )
+                    .changed();
+                if ui.small_button("x").clicked() {
+                    o_delete_ix = Some(ix);
+                }
+            }
+            if let Some(delete_ix) = o_delete_ix {
+                rule.weighted_transitions.remove(delete_ix);
+                changed = true;
+            }
+            if bweighted && ui.small_button("+").clicked() {
+                rule.weighted_transitions.push((rule.state, 1.0));
+                changed = true;
+            }
+        });
+        changed
     }
 
     fn edit_cell_type(ui: &mut Ui, cell: &mut CellType) -> bool {
@@ -406,8 +1454,25 @@ impl<const CW: usize> RState<CW> {
     }
 
     fn edit_conv_matrix_ui(&mut self, ui: &mut Ui) {
+        self.interaction_kind_ui(ui);
+        // the diffusion kernels in the reaction diffusion mode,
+        // otherwise the kernels of the selected region if it has its own
+        let selected_region = self.config.selected_region;
+        let inner = if self.config.sim_mode == SimMode::ReactionDiffusion {
+            &mut self.reaction_diffusion.kernels
+        } else {
+            match selected_region
+                .checked_sub(1)
+                .and_then(|ix| self.regions.get_mut(ix))
+                .and_then(|region| region.kernels.as_mut())
+            {
+                Some(kernels) => kernels,
+                None => &mut self.conv_kernels,
+            }
+        };
         let mut convolution_wrapper = ConvWrapper {
-            inner: &mut self.conv_kernels,
+            inner,
+            interaction: &mut self.interaction,
             config: &mut self.config,
             cell_type_map: &mut self.cell_type_map,
             ui_down_arrow: &mut self.ui_down_arrow,
@@ -427,6 +1492,27 @@ impl<const CW: usize> RState<CW> {
         convolution_wrapper.copy_kernels();
     }
 
+    fn interaction_kind_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("pairwise interaction:");
+            let kind = match self.interaction {
+                None => 0,
+                Some(Interaction::Weights(_)) => 1,
+                Some(Interaction::Kernels(_)) => 2,
+            };
+            for (new_kind, name) in ["none", "weights", "kernels"].into_iter().enumerate() {
+                if ui.radio(kind == new_kind, name).clicked() && kind != new_kind {
+                    self.interaction = match new_kind {
+                        1 => Some(Interaction::neutral_weights()),
+                        2 => Some(Interaction::kernels_from(&self.conv_kernels)),
+                        _ => None,
+                    };
+                    self.everything_changed();
+                }
+            }
+        });
+    }
+
     fn edit_symmetry(&mut self, ui: &mut Ui) {
         let symmetries = [
             Symmetry::X,
@@ -464,6 +1550,25 @@ impl<const CW: usize> RState<CW> {
             if save_file.cells.is_some() {
                 Self::select_bool_ui(ui, &mut save_file.include_cells, "cells");
             }
+            if save_file.masks.is_some() {
+                Self::select_bool_ui(ui, &mut save_file.include_masks, "masks");
+            }
+            if save_file.regions.is_some() {
+                Self::select_bool_ui(ui, &mut save_file.include_regions, "regions");
+            }
+            if save_file.reaction_diffusion.is_some() {
+                Self::select_bool_ui(
+                    ui,
+                    &mut save_file.include_reaction_diffusion,
+                    "reaction diffusion",
+                );
+            }
+            if save_file.timeline.is_some() {
+                Self::select_bool_ui(ui, &mut save_file.include_timeline, "timeline");
+            }
+            if save_file.scripts.is_some() {
+                Self::select_bool_ui(ui, &mut save_file.include_scripts, "scripts");
+            }
             if ui.button("Load").clicked() {
                 self.load_save_file();
                 self.config.mode = UiMode::Main;
@@ -490,6 +1595,18 @@ impl<const CW: usize> RState<CW> {
                 include_rules: true,
                 include_cell_type_map: true,
                 include_cells: true,
+                reaction_diffusion: Some(self.reaction_diffusion.params.clone()),
+                include_reaction_diffusion: self.config.sim_mode == SimMode::ReactionDiffusion,
+                masks: Some(self.masks.clone()),
+                include_masks: true,
+                regions: Some(self.regions.clone()),
+                region_maps: Some(self.region_maps.clone()),
+                include_regions: !self.regions.is_empty(),
+                timeline: Some(self.timeline.clone()),
+                include_timeline: !self.timeline.is_empty(),
+                scripts: Some(self.script.sources.clone()),
+                include_scripts: true,
+                diffusion_kernels: Some(self.reaction_diffusion.kernels),
             }
         });
         Self::save_file_controls(&mut self.config.mode, save_file, ui);
@@ -501,6 +1618,15 @@ impl<const CW: usize> RState<CW> {
         Self::select_bool_ui(ui, &mut save_file.include_rules, "rules");
         Self::select_bool_ui(ui, &mut save_file.include_cell_type_map, "cell_type_map");
         Self::select_bool_ui(ui, &mut save_file.include_cells, "cells");
+        Self::select_bool_ui(ui, &mut save_file.include_masks, "masks");
+        Self::select_bool_ui(ui, &mut save_file.include_regions, "regions");
+        Self::select_bool_ui(
+            ui,
+            &mut save_file.include_reaction_diffusion,
+            "reaction diffusion",
+        );
+        Self::select_bool_ui(ui, &mut save_file.include_timeline, "timeline");
+        Self::select_bool_ui(ui, &mut save_file.include_scripts, "scripts");
         if ui.button("Save as ...").clicked() {
             if let Some(path_buf) = FileDialog::new().save_file() {
                 if let Some(file_path) = path_buf.to_str() {
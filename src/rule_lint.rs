@@ -0,0 +1,263 @@
+use crate::{
+    interaction::Interaction,
+    reduction::Reduction,
+    rules::{Rule, RuleSet},
+    FieldType,
+};
+use matrices::traits::Matrix;
+use std::ops::RangeInclusive;
+
+/// A problem with the rule at index `rule`
+pub struct Lint {
+    pub rule: usize,
+    pub message: String,
+}
+
+/// What the linter knows about the kernel used by every cell type
+pub struct KernelInfo {
+    /// values the accumulator can take, None if they are unknown
+    pub bounds: [Option<RangeInclusive<FieldType>>; 9],
+    /// true for kernels which are 0 everywhere, only known with multiple kernels
+    pub bempty: [bool; 9],
+}
+
+impl KernelInfo {
+    /// `values` are the values of the cell types in the `CellTypeMap`
+    /// with `interaction` the weights depend on the type of the neighbour as well
+    pub fn new<C: Matrix<FieldType>>(
+        kernels: &[C; 9],
+        bsingle_kernel: bool,
+        interaction: Option<&Interaction<FieldType, C>>,
+        reductions: &[Reduction; 9],
+        values: &[FieldType],
+    ) -> Self {
+        let mut bounds: [Option<RangeInclusive<FieldType>>; 9] = Default::default();
+        let mut bempty = [false; 9];
+        for ix in 0..9 {
+            let kernel_ix = if bsingle_kernel { 0 } else { ix };
+            let kernel = &kernels[kernel_ix];
+            // the weights of every position of the kernel for every neighbour type, positions
+            // where all of them are 0 are left out
+            let positions: Vec<Vec<(FieldType, FieldType)>> = (0..kernel.height())
+                .flat_map(|y| (0..kernel.width()).map(move |x| (x, y)))
+                .map(|xy| {
+                    values
+                        .iter()
+                        .enumerate()
+                        .map(|(neighbour, value)| {
+                            let weight = match interaction {
+                                None => kernel.index(xy),
+                                Some(Interaction::Weights(weights)) => {
+                                    kernel.index(xy) * weights[ix][neighbour]
+                                }
+                                Some(Interaction::Kernels(kernels)) => {
+                                    kernels[ix][neighbour].index(xy)
+                                }
+                            };
+                            (weight, *value)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .filter(|weights| weights.iter().any(|(weight, _)| *weight != 0.0))
+                .collect();
+            bempty[ix] = (interaction.is_some() || !bsingle_kernel) && positions.is_empty();
+            bounds[ix] = Self::bounds(&positions, reductions[kernel_ix], values);
+        }
+        KernelInfo { bounds, bempty }
+    }
+
+    /// smallest and largest accumulated value, cells outside of the board count as 0
+    /// `positions` holds the weight and the value of every neighbour type for every position of the kernel
+    fn bounds(
+        positions: &[Vec<(FieldType, FieldType)>],
+        reduction: Reduction,
+        values: &[FieldType],
+    ) -> Option<RangeInclusive<FieldType>> {
+        let min_value = values.iter().copied().fold(0.0, FieldType::min);
+        let max_value = values.iter().copied().fold(0.0, FieldType::max);
+        let contribution_bounds = |weights: &Vec<(FieldType, FieldType)>| {
+            weights.iter().map(|(weight, value)| weight * value).fold(
+                (0.0, 0.0),
+                |(lo, hi): (FieldType, FieldType), contribution| {
+                    (lo.min(contribution), hi.max(contribution))
+                },
+            )
+        };
+        if positions.is_empty() {
+            return Some(0.0..=0.0);
+        }
+        match reduction {
+            Reduction::Sum => {
+                let (lo, hi) = positions.iter().fold((0.0, 0.0), |(lo, hi), weights| {
+                    let (a, b) = contribution_bounds(weights);
+                    (lo + a, hi + b)
+                });
+                Some(lo..=hi)
+            }
+            Reduction::Max | Reduction::Min | Reduction::Median => {
+                let (lo, hi) = positions
+                    .iter()
+                    .map(contribution_bounds)
+                    .fold((FieldType::MAX, FieldType::MIN), |(lo, hi), (a, b)| {
+                        (lo.min(a), hi.max(b))
+                    });
+                Some(lo..=hi)
+            }
+            Reduction::CountNonZero => Some(0.0..=positions.len() as FieldType),
+            Reduction::WeightedMean
+                if positions.iter().flatten().all(|(weight, _)| *weight >= 0.0) =>
+            {
+                Some(min_value..=max_value)
+            }
+            Reduction::WeightedMean | Reduction::Product => None,
+        }
+    }
+}
+
+fn is_empty(range: &RangeInclusive<FieldType>) -> bool {
+    range.start() > range.end()
+}
+
+fn overlaps(a: &RangeInclusive<FieldType>, b: &RangeInclusive<FieldType>) -> bool {
+    a.start() <= b.end() && b.start() <= a.end()
+}
+
+/// true if `rule` always matches when `other` matches, the age is taken into account
+fn age_covers(rule: &Rule<FieldType>, other: &Rule<FieldType>) -> bool {
+    match (&rule.age_range, &other.age_range) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(a), Some(b)) => a.start() <= b.start() && b.end() <= a.end(),
+    }
+}
+
+/// true if the union of `ranges` contains all of `range`
+fn covered_by(
+    range: &RangeInclusive<FieldType>,
+    mut ranges: Vec<RangeInclusive<FieldType>>,
+) -> bool {
+    ranges.sort_by(|a, b| a.start().total_cmp(b.start()));
+    let mut reached = *range.start();
+    for other in ranges {
+        if *other.start() > reached {
+            // nothing covers the values between reached and other.start()
+            return false;
+        }
+        if *other.end() >= reached {
+            reached = *other.end();
+            if reached >= *range.end() {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// problems of the rules which can be found without running them
+pub fn lint_rules(rules: &RuleSet<FieldType>, kernels: &KernelInfo) -> Vec<Lint> {
+    let mut res = Vec::new();
+    for (ix, rule) in rules.rules.iter().enumerate() {
+        let mut lint = |message: String| res.push(Lint { rule: ix, message });
+        if is_empty(&rule.range) {
+            lint("the range is empty, start > end".to_string());
+            continue;
+        }
+        if let Some(age_range) = &rule.age_range {
+            if age_range.start() > age_range.end() {
+                lint("the age range is empty, start > end".to_string());
+                continue;
+            }
+        }
+        if kernels.bempty[rule.state.as_index()] {
+            lint(format!("the kernel of {} is 0 everywhere", rule.state));
+        }
+        if kernels.bempty[rule.transition.as_index()] && rule.transition != rule.state {
+            lint(format!("the kernel of {} is 0 everywhere", rule.transition));
+        }
+        if let Some(bounds) = &kernels.bounds[rule.state.as_index()] {
+            if !overlaps(&rule.range, bounds) {
+                lint(format!(
+                    "the accumulated value of {} is always in {}..={}",
+                    rule.state,
+                    bounds.start(),
+                    bounds.end()
+                ));
+            }
+        }
+        if rules.bcompeting {
+            // all matching rules take part, their order does not matter
+            continue;
+        }
+        let earlier: Vec<(usize, &Rule<FieldType>)> = rules.rules[..ix]
+            .iter()
+            .enumerate()
+            .filter(|(_, other)| {
+                other.state == rule.state
+                    && !is_empty(&other.range)
+                    && overlaps(&other.range, &rule.range)
+            })
+            .collect();
+        let shadowing: Vec<RangeInclusive<FieldType>> = earlier
+            .iter()
+            .filter(|(_, other)| other.transition_probability.is_none() && age_covers(other, rule))
+            .map(|(_, other)| other.range.clone())
+            .collect();
+        if covered_by(&rule.range, shadowing) {
+            lint("never fires, earlier rules for the same state match first".to_string());
+        } else if let Some((other_ix, _)) = earlier.first() {
+            lint(format!(
+                "the range overlaps with rule {other_ix} which is checked first"
+            ));
+        }
+    }
+    res
+}
+
+#[test]
+fn shadowed_and_empty_rules() {
+    use crate::cell_type::CellType;
+    let rules = RuleSet::new(vec![
+        Rule::new(CellType::A, 0.0..=2.0, CellType::NoCell),
+        Rule::new(CellType::A, 2.0..=4.0, CellType::NoCell),
+        Rule::new(CellType::A, 1.0..=3.0, CellType::B),
+        Rule::new(CellType::A, 5.0..=4.0, CellType::B),
+        Rule::new(CellType::NoCell, 3.0..=3.0, CellType::A),
+    ]);
+    let kernels = KernelInfo {
+        bounds: Default::default(),
+        bempty: [false; 9],
+    };
+    let lints: Vec<(usize, String)> = lint_rules(&rules, &kernels)
+        .into_iter()
+        .map(|lint| (lint.rule, lint.message))
+        .collect();
+    assert_eq!(lints.len(), 3);
+    assert!(lints[0].0 == 1 && lints[0].1.contains("overlaps"));
+    assert!(lints[1].0 == 2 && lints[1].1.contains("never fires"));
+    assert!(lints[2].0 == 3 && lints[2].1.contains("empty"));
+}
+
+#[test]
+fn bounds_with_interaction() {
+    use matrices::const_matrix::ConstMatrix;
+    // two weights of 1 in the corners
+    let kernel: ConstMatrix<FieldType, 3, 3> =
+        ConstMatrix::new_with(3, 3, |(x, y)| if x == y && x != 1 { 1.0 } else { 0.0 });
+    let kernels = [kernel; 9];
+    let values = [0.0, 1.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+    let reductions = [Reduction::Sum; 9];
+    let info = KernelInfo::new(&kernels, true, None, &reductions, &values);
+    assert_eq!(info.bounds[1], Some(0.0..=4.0));
+    let mut weights = [[1.0; 9]; 9];
+    weights[1][2] = -3.0;
+    let interaction = Interaction::Weights(weights);
+    let info = KernelInfo::new(&kernels, true, Some(&interaction), &reductions, &values);
+    assert_eq!(info.bounds[1], Some(-12.0..=2.0));
+    assert_eq!(info.bounds[2], Some(0.0..=4.0));
+    let mut kernels_table = [[kernel; 9]; 9];
+    kernels_table[1] = [ConstMatrix::new(3, 3, 0.0); 9];
+    let interaction = Interaction::Kernels(Box::new(kernels_table));
+    let info = KernelInfo::new(&kernels, true, Some(&interaction), &reductions, &values);
+    assert_eq!(info.bounds[1], Some(0.0..=0.0));
+    assert!(info.bempty[1] && !info.bempty[2]);
+}
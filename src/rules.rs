@@ -1,7 +1,8 @@
 use crate::{CellType, FieldType};
 use num_traits::{AsPrimitive, One, Zero};
+use quad_rand::gen_range;
 use serde::{Deserialize, Serialize};
-use std::ops::RangeInclusive;
+use std::{fmt::Display, ops::RangeInclusive};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Rule<T: Copy> {
@@ -9,89 +10,278 @@ pub struct Rule<T: Copy> {
     pub range: RangeInclusive<T>,
     pub transition: CellType,
     pub transition_probability: Option<f32>,
+    /// weighted distribution over target cell types
+    /// `transition` is used if this is empty
+    pub weighted_transitions: Vec<(CellType, f32)>,
+    /// the rule only matches cells which have been in their state for this many generations
+    /// requires age tracking, without it rules with an age range never match
+    pub age_range: Option<RangeInclusive<u32>>,
+}
+
+impl<T: Copy> Rule<T> {
+    pub fn new(state: CellType, range: RangeInclusive<T>, transition: CellType) -> Self {
+        Rule {
+            state,
+            range,
+            transition,
+            transition_probability: None,
+            weighted_transitions: Vec::new(),
+            age_range: None,
+        }
+    }
+
+    /// weight of this rule when all matching rules compete,
+    /// the probability if there is one, deterministic rules always take part with 1
+    pub fn weight(&self) -> f32 {
+        self.transition_probability.unwrap_or(1.0)
+    }
+
+    pub fn matches(&self, state: CellType, convolution: T, age: Option<u32>) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.state == state
+            && self.range.contains(&convolution)
+            && match (&self.age_range, age) {
+                (None, _) => true,
+                (Some(age_range), Some(age)) => age_range.contains(&age),
+                (Some(_), None) => false,
+            }
+    }
+
+    /// why this rule does not match, None if it matches
+    pub fn mismatch(&self, state: CellType, convolution: T, age: Option<u32>) -> Option<String>
+    where
+        T: PartialOrd + Display,
+    {
+        if self.state != state {
+            return Some(format!("the cell is {state}, not {}", self.state));
+        }
+        if !self.range.contains(&convolution) {
+            return Some(format!(
+                "{convolution} is not in {}..={}",
+                self.range.start(),
+                self.range.end()
+            ));
+        }
+        match (&self.age_range, age) {
+            (Some(age_range), Some(age)) if !age_range.contains(&age) => Some(format!(
+                "the age {age} is not in {}..={}",
+                age_range.start(),
+                age_range.end()
+            )),
+            (Some(_), None) => Some("the rule has an age range but ages are not tracked".into()),
+            _ => None,
+        }
+    }
+
+    /// true if the outcome of this rule involves chance,
+    /// the probability only counts with `bprobabilities`, the weighted transitions always
+    pub fn is_random(&self, bprobabilities: bool) -> bool {
+        (bprobabilities && self.transition_probability.is_some())
+            || !self.weighted_transitions.is_empty()
+    }
+
+    /// the transition of the rule, drawn from the weighted transitions if there are any
+    pub fn random_transition(&self) -> CellType {
+        choose_weighted(self.weighted_transitions.iter().copied()).unwrap_or(self.transition)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct RuleSet<T: Copy> {
     pub rules: Vec<Rule<T>>,
+    /// if set all matching rules compete by weight instead of the first match firing
+    pub bcompeting: bool,
 }
 
 impl<T: Copy + PartialEq + PartialOrd> RuleSet<T> {
-    pub fn apply(&self, initial_value: CellType, convolution: T) -> CellType {
-        for rule in &self.rules {
-            if rule.state == initial_value && rule.range.contains(&convolution) {
-                return rule.transition;
-            }
+    pub fn new(rules: Vec<Rule<T>>) -> Self {
+        RuleSet {
+            rules,
+            bcompeting: false,
+        }
+    }
+
+    /// `age` is None if ages are not tracked
+    pub fn apply(&self, initial_value: CellType, convolution: T, age: Option<u32>) -> CellType {
+        match self.firing_rule(initial_value, convolution, age) {
+            Some(ix) => self.rules[ix].random_transition(),
+            // you think the rules don't apply to you Mr. Anderson
+            None => initial_value,
         }
+    }
 
-        // you think the rules don't apply to you Mr. Anderson
-        initial_value
+    /// index of the rule `apply` uses, None if no rule matches
+    /// the probabilities of the rules are ignored, competing rules all have the same weight
+    pub fn firing_rule(
+        &self,
+        initial_value: CellType,
+        convolution: T,
+        age: Option<u32>,
+    ) -> Option<usize> {
+        if self.bcompeting {
+            return self.competing_rule(initial_value, convolution, age, false);
+        }
+        self.rules
+            .iter()
+            .position(|rule| rule.matches(initial_value, convolution, age))
     }
 
-    pub fn apply_random(&self, initial_value: CellType, convolution: T) -> CellType {
-        for rule in &self.rules {
-            if rule.state == initial_value && rule.range.contains(&convolution) {
+    pub fn apply_random(
+        &self,
+        initial_value: CellType,
+        convolution: T,
+        age: Option<u32>,
+    ) -> CellType {
+        match self.firing_rule_random(initial_value, convolution, age) {
+            Some(ix) => self.rules[ix].random_transition(),
+            // you think the rules don't apply to you Mr. Anderson
+            None => initial_value,
+        }
+    }
+
+    /// index of the rule `apply_random` uses, None if no rule fires
+    pub fn firing_rule_random(
+        &self,
+        initial_value: CellType,
+        convolution: T,
+        age: Option<u32>,
+    ) -> Option<usize> {
+        if self.bcompeting {
+            return self.competing_rule(initial_value, convolution, age, true);
+        }
+        for (ix, rule) in self.rules.iter().enumerate() {
+            if rule.matches(initial_value, convolution, age) {
                 let bfire = rule
                     .transition_probability
-                    .map(|prob| quad_rand::gen_range(0.0, 1.0) <= prob)
+                    .map(|prob| gen_range(0.0, 1.0) <= prob)
                     .unwrap_or(true);
                 if bfire {
-                    return rule.transition;
+                    return Some(ix);
                 }
             }
         }
+        None
+    }
+
+    /// true if `apply_random` with `bprobabilities` or `apply` without them
+    /// can give a different result for the same input
+    /// cells like this have to be evaluated again in the next generation
+    /// even if nothing around them changed
+    pub fn is_random(
+        &self,
+        initial_value: CellType,
+        convolution: T,
+        age: Option<u32>,
+        bprobabilities: bool,
+    ) -> bool {
+        let mut num_matching = 0;
+        for rule in self
+            .rules
+            .iter()
+            .filter(|rule| rule.matches(initial_value, convolution, age))
+        {
+            if rule.is_random(bprobabilities) {
+                return true;
+            }
+            if !self.bcompeting {
+                // the first deterministic match always fires
+                return false;
+            }
+            num_matching += 1;
+        }
+        num_matching > 1
+    }
+
+    /// true if the outcome of some cell can involve chance, see `is_random`
+    pub fn involves_chance(&self, bprobabilities: bool) -> bool {
+        (self.bcompeting && self.rules.len() > 1)
+            || self.rules.iter().any(|rule| rule.is_random(bprobabilities))
+    }
 
-        // you think the rules don't apply to you Mr. Anderson
-        initial_value
+    /// true if a rule for `state` depends on the age
+    /// cells like this have to be evaluated again in every generation
+    pub fn has_age_rules(&self, state: CellType) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.state == state && rule.age_range.is_some())
+    }
+
+    /// picks one of the matching rules proportional to its weight
+    /// the cell keeps its state with the weight left to 1, so a single rule
+    /// fires with its probability and weights above 1 in total are normalised
+    /// without `bprobabilities` every rule has the weight 1
+    fn competing_rule(
+        &self,
+        initial_value: CellType,
+        convolution: T,
+        age: Option<u32>,
+        bprobabilities: bool,
+    ) -> Option<usize> {
+        let matching = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.matches(initial_value, convolution, age))
+            .map(|(ix, rule)| (Some(ix), if bprobabilities { rule.weight() } else { 1.0 }));
+        let stay = 1.0
+            - matching
+                .clone()
+                .map(|(_, weight)| weight.max(0.0))
+                .sum::<f32>();
+        choose_weighted(matching.chain(std::iter::once((None, stay)))).flatten()
     }
 }
 
-pub fn classic_rules() -> RuleSet<FieldType> {
-    RuleSet {
-        rules: vec![
-            Rule {
-                state: CellType::A,
-                range: Zero::zero()..=One::one(),
-                transition: CellType::NoCell,
-                transition_probability: None,
-            },
-            Rule {
-                state: CellType::A,
-                range: 4_u8.as_()..=8_u8.as_(),
-                transition: CellType::NoCell,
-                transition_probability: None,
-            },
-            Rule {
-                state: CellType::NoCell,
-                range: 3_u8.as_()..=3_u8.as_(),
-                transition: CellType::A,
-                transition_probability: None,
-            },
-        ],
+/// picks one item with a probability proportional to its weight
+/// returns None if there is no item with a positive weight
+fn choose_weighted<X: Copy>(items: impl Iterator<Item = (X, f32)> + Clone) -> Option<X> {
+    let total: f32 = items.clone().map(|(_, weight)| weight.max(0.0)).sum();
+    if total <= 0.0 {
+        return None;
     }
+    let mut choice = gen_range(0.0, total);
+    let mut last = None;
+    for (item, weight) in items.filter(|(_, weight)| *weight > 0.0) {
+        if choice < weight {
+            return Some(item);
+        }
+        choice -= weight;
+        last = Some(item);
+    }
+    // rounding errors
+    last
+}
+
+pub fn classic_rules() -> RuleSet<FieldType> {
+    RuleSet::new(vec![
+        Rule::new(CellType::A, Zero::zero()..=One::one(), CellType::NoCell),
+        Rule::new(CellType::A, 4_u8.as_()..=8_u8.as_(), CellType::NoCell),
+        Rule::new(CellType::NoCell, 3_u8.as_()..=3_u8.as_(), CellType::A),
+    ])
 }
 
 pub fn flame_rules() -> RuleSet<FieldType> {
-    RuleSet {
-        rules: vec![
-            Rule {
-                state: CellType::A,
-                range: 0_u8.as_()..=3_u8.as_(),
-                transition: CellType::NoCell,
-                transition_probability: None,
-            },
-            Rule {
-                state: CellType::A,
-                range: 10_u8.as_()..=28_u8.as_(),
-                transition: CellType::NoCell,
-                transition_probability: None,
-            },
-            Rule {
-                state: CellType::NoCell,
-                range: 6_u8.as_()..=8_u8.as_(),
-                transition: CellType::A,
-                transition_probability: None,
-            },
-        ],
-    }
+    RuleSet::new(vec![
+        Rule::new(CellType::A, 0_u8.as_()..=3_u8.as_(), CellType::NoCell),
+        Rule::new(CellType::A, 10_u8.as_()..=28_u8.as_(), CellType::NoCell),
+        Rule::new(CellType::NoCell, 6_u8.as_()..=8_u8.as_(), CellType::A),
+    ])
+}
+
+#[test]
+fn competing_rule_fires_with_its_probability() {
+    let mut rules = RuleSet::new(vec![Rule {
+        transition_probability: Some(0.3),
+        ..Rule::new(CellType::NoCell, 0.0..=1.0, CellType::A)
+    }]);
+    rules.bcompeting = true;
+    quad_rand::srand(7);
+    let fired = (0..10_000)
+        .filter(|_| rules.apply_random(CellType::NoCell, 0.5, None) == CellType::A)
+        .count();
+    assert!((2_700..3_300).contains(&fired), "fired {fired} times");
+    rules.rules[0].transition_probability = None;
+    assert_eq!(rules.apply_random(CellType::NoCell, 0.5, None), CellType::A);
 }
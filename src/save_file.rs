@@ -1,12 +1,29 @@
 use crate::{
     cell_type::{CellType, CellTypeMap},
-    rules::RuleSet,
+    mask::Mask,
+    reaction_diffusion::ReactionParams,
+    region::Region,
+    rules::{Rule, RuleSet},
+    script::ScriptSources,
+    timeline::Timeline,
     ConvolutionMatrix, FieldType,
 };
+use bincode::Options;
 use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
 use matrices::vec_matrix::VecMatrix;
 use serde::{Deserialize, Serialize};
-use std::io::{Read, Write};
+use std::{
+    io::{Read, Write},
+    ops::RangeInclusive,
+};
+
+/// written in front of every save file, files without it were saved before the layout was versioned
+const MAGIC: &[u8; 8] = b"rugolsav";
+
+/// has to be increased when the layout of `SaveFile` changes,
+/// files of the previous versions need a migration in `load_from_bytes`
+/// 0 and 1 are the unversioned layouts of `RuleV0` and `RuleV1`
+pub const SAVE_FILE_VERSION: u32 = 2;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub enum ConvMatrixE<const CW: usize> {
@@ -24,11 +41,24 @@ pub struct SaveFile<const CW: usize> {
     pub include_rules: bool,
     pub include_cell_type_map: bool,
     pub include_cells: bool,
+    pub reaction_diffusion: Option<ReactionParams>,
+    pub include_reaction_diffusion: bool,
+    pub masks: Option<Vec<VecMatrix<Mask>>>,
+    pub include_masks: bool,
+    pub regions: Option<Vec<Region<ConvolutionMatrix<CW>>>>,
+    pub region_maps: Option<Vec<VecMatrix<u8>>>,
+    pub include_regions: bool,
+    pub timeline: Option<Timeline>,
+    pub include_timeline: bool,
+    pub scripts: Option<ScriptSources>,
+    pub include_scripts: bool,
+    /// loaded with `reaction_diffusion`
+    pub diffusion_kernels: Option<[ConvolutionMatrix<CW>; 9]>,
 }
 
 impl<const CW: usize> SaveFile<CW> {
     pub fn save_to(&self, filename: &str) -> Result<(), bincode::Error> {
-        let bytes = bincode::serialize(self)?;
+        let bytes = bincode::serialize(&(MAGIC, SAVE_FILE_VERSION, self))?;
         let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
         e.write_all(&bytes)?;
         let mut file = std::fs::File::create(filename)?;
@@ -41,6 +71,169 @@ impl<const CW: usize> SaveFile<CW> {
         let mut decoder = ZlibDecoder::new(encoded);
         let mut serialized_bytes = Vec::new();
         decoder.read_to_end(&mut serialized_bytes)?;
-        bincode::deserialize::<Self>(&serialized_bytes)
+        let versioned = match serialized_bytes.strip_prefix(MAGIC) {
+            Some(versioned) => versioned,
+            None => return Self::load_unversioned(&serialized_bytes),
+        };
+        let version: u32 = bincode::deserialize(versioned)?;
+        let bytes = &versioned[std::mem::size_of::<u32>()..];
+        match version {
+            SAVE_FILE_VERSION => bincode::deserialize::<Self>(bytes),
+            _ => Err(Box::new(bincode::ErrorKind::Custom(format!(
+                "the file has the version {version}, this build reads up to version {SAVE_FILE_VERSION}"
+            )))),
+        }
     }
+
+    /// the unversioned layouts only differ in the rules, a layout has to consume all bytes to match
+    fn load_unversioned(bytes: &[u8]) -> Result<SaveFile<CW>, bincode::Error> {
+        let options = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .reject_trailing_bytes();
+        options
+            .deserialize::<UnversionedSaveFile<RuleV1, CW>>(bytes)
+            .map(Self::from)
+            .or_else(|err| {
+                options
+                    .deserialize::<UnversionedSaveFile<RuleV0, CW>>(bytes)
+                    .map(Self::from)
+                    .map_err(|_| err)
+            })
+    }
+}
+
+/// the layout of the save files from before the versioning
+#[derive(Deserialize)]
+struct UnversionedSaveFile<R, const CW: usize> {
+    convolution: Option<ConvMatrixE<CW>>,
+    rules: Option<UnversionedRuleSet<R>>,
+    cell_type_map: Option<CellTypeMap>,
+    cells: Option<Vec<VecMatrix<CellType>>>,
+    include_convolution: bool,
+    include_rules: bool,
+    include_cell_type_map: bool,
+    include_cells: bool,
+}
+
+#[derive(Deserialize)]
+struct UnversionedRuleSet<R> {
+    rules: Vec<R>,
+}
+
+/// rules from before transition probabilities, the shipped presets use them
+#[derive(Deserialize)]
+struct RuleV0 {
+    state: CellType,
+    range: RangeInclusive<FieldType>,
+    transition: CellType,
+}
+
+/// rules from before weighted transitions and age ranges
+#[derive(Deserialize)]
+struct RuleV1 {
+    state: CellType,
+    range: RangeInclusive<FieldType>,
+    transition: CellType,
+    transition_probability: Option<f32>,
+}
+
+impl From<RuleV0> for Rule<FieldType> {
+    fn from(rule: RuleV0) -> Self {
+        Rule::new(rule.state, rule.range, rule.transition)
+    }
+}
+
+impl From<RuleV1> for Rule<FieldType> {
+    fn from(rule: RuleV1) -> Self {
+        Rule {
+            transition_probability: rule.transition_probability,
+            ..Rule::new(rule.state, rule.range, rule.transition)
+        }
+    }
+}
+
+impl<R: Into<Rule<FieldType>>, const CW: usize> From<UnversionedSaveFile<R, CW>> for SaveFile<CW> {
+    fn from(old: UnversionedSaveFile<R, CW>) -> Self {
+        let rules = old
+            .rules
+            .map(|rule_set| RuleSet::new(rule_set.rules.into_iter().map(Into::into).collect()));
+        SaveFile {
+            convolution: old.convolution,
+            rules,
+            cell_type_map: old.cell_type_map,
+            cells: old.cells,
+            include_convolution: old.include_convolution,
+            include_rules: old.include_rules,
+            include_cell_type_map: old.include_cell_type_map,
+            include_cells: old.include_cells,
+            reaction_diffusion: None,
+            include_reaction_diffusion: false,
+            masks: None,
+            include_masks: false,
+            regions: None,
+            region_maps: None,
+            include_regions: false,
+            timeline: None,
+            include_timeline: false,
+            scripts: None,
+            include_scripts: false,
+            diffusion_kernels: None,
+        }
+    }
+}
+
+#[test]
+fn loads_the_presets() {
+    for (name, bytes, num_rules) in [
+        ("conway", &include_bytes!("../presets/conway")[..], 3),
+        (
+            "sierpinski",
+            &include_bytes!("../presets/sierpinski")[..],
+            1,
+        ),
+    ] {
+        let save_file = SaveFile::<7>::load_from_bytes(bytes)
+            .unwrap_or_else(|err| panic!("{name} does not load: {err}"));
+        assert!(save_file.convolution.is_some(), "{name}");
+        assert_eq!(
+            save_file.rules.map(|rules| rules.rules.len()),
+            Some(num_rules)
+        );
+        assert!(save_file.cell_type_map.is_some(), "{name}");
+        assert!(
+            save_file.cells.is_some_and(|cells| !cells.is_empty()),
+            "{name}"
+        );
+    }
+    let conway = SaveFile::<7>::load_from_bytes(include_bytes!("../presets/conway")).unwrap();
+    let path = std::env::temp_dir().join("rugol_save_file_round_trip");
+    let path = path.to_str().unwrap();
+    conway.save_to(path).unwrap();
+    let reloaded = SaveFile::<7>::load_from_bytes(&std::fs::read(path).unwrap()).unwrap();
+    let _ = std::fs::remove_file(path);
+    assert_eq!(
+        reloaded.rules.map(|rules| rules.rules.len()),
+        Some(3),
+        "a versioned save file loads again"
+    );
+}
+
+#[test]
+fn migrates_rules_with_probabilities() {
+    let rules = vec![(CellType::A, 2.0_f32..=3.0, CellType::NoCell, Some(0.5_f32))];
+    let unversioned = (
+        None::<ConvMatrixE<7>>,
+        Some((rules,)),
+        None::<CellTypeMap>,
+        None::<Vec<VecMatrix<CellType>>>,
+        [false, true, false, false],
+    );
+    let mut e = ZlibEncoder::new(Vec::new(), Compression::default());
+    e.write_all(&bincode::serialize(&unversioned).unwrap())
+        .unwrap();
+    let save_file = SaveFile::<7>::load_from_bytes(&e.finish().unwrap()).unwrap();
+    let rules = save_file.rules.unwrap();
+    assert_eq!(rules.rules[0].range, 2.0..=3.0);
+    assert_eq!(rules.rules[0].transition_probability, Some(0.5));
+    assert!(save_file.include_rules && !save_file.include_cells);
 }
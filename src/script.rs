@@ -0,0 +1,237 @@
+use crate::{cell_type::CellType, FieldType};
+use matrices::{traits::Matrix, vec_matrix::VecMatrix};
+use rhai::{Array, Engine, Scope, AST, FLOAT, INT};
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+/// `step(n)` asks for at most this many steps
+pub const MAX_SCRIPT_STEPS: usize = 100_000;
+
+/// Something an automation script asks for, the commands are run in order after the script finished
+pub enum ScriptCommand {
+    Step(usize),
+    SetCell((usize, usize), CellType),
+    Stamp((usize, usize), VecMatrix<CellType>),
+    SetValue(CellType, FieldType),
+    Pause,
+}
+
+/// The sources of the scripts, this is what gets saved
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ScriptSources {
+    /// defines `fn next(state, acc, x, y, gen)` which returns the next state of a cell
+    pub rule: String,
+    pub automation: String,
+}
+
+/// Sandboxed Rhai scripts for the transition function and for automation
+/// cell types are passed to and from scripts as integers 0 (NoCell) ..= 8 (H)
+pub struct Script {
+    engine: Engine,
+    pub sources: ScriptSources,
+    /// None if the rule script does not compile or has no `next` function
+    rule_ast: Option<AST>,
+    pub error: Option<String>,
+    commands: Rc<RefCell<Vec<ScriptCommand>>>,
+    /// commands of the last automation run which did not run yet, steps are spread over frames
+    pub queue: VecDeque<ScriptCommand>,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Script {
+    pub fn new() -> Self {
+        let commands = Rc::new(RefCell::new(Vec::new()));
+        let mut engine = Engine::new();
+        // a script must not be able to hang the app
+        engine
+            .set_max_operations(1_000_000)
+            .set_max_call_levels(32)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(10_000)
+            .set_max_array_size(100_000);
+        let c = commands.clone();
+        engine.register_fn("step", move |n: INT| {
+            c.borrow_mut().push(ScriptCommand::Step(
+                (n.max(0) as usize).min(MAX_SCRIPT_STEPS),
+            ))
+        });
+        let c = commands.clone();
+        engine.register_fn("set_cell", move |x: INT, y: INT, state: INT| {
+            if let Some(ix) = Self::index(x, y) {
+                c.borrow_mut()
+                    .push(ScriptCommand::SetCell(ix, Self::cell_type(state)));
+            }
+        });
+        let c = commands.clone();
+        engine.register_fn("stamp", move |x: INT, y: INT, rows: Array| {
+            if let Some(ix) = Self::index(x, y) {
+                c.borrow_mut()
+                    .push(ScriptCommand::Stamp(ix, Self::pattern(rows)));
+            }
+        });
+        let c = commands.clone();
+        engine.register_fn("set_value", move |state: INT, value: FLOAT| {
+            c.borrow_mut().push(ScriptCommand::SetValue(
+                Self::cell_type(state),
+                value as FieldType,
+            ))
+        });
+        let c = commands.clone();
+        engine.register_fn("pause", move || c.borrow_mut().push(ScriptCommand::Pause));
+        let mut res = Script {
+            engine,
+            sources: ScriptSources {
+                rule: "// Conway's game of life\nfn next(state, acc, x, y, gen) {\n    if acc == 3.0 || (state == 1 && acc == 2.0) { 1 } else { 0 }\n}\n".to_string(),
+                automation: "// step(n), set_cell(x, y, state), stamp(x, y, [[0, 1], [1, 0]]),\n// set_value(state, value), pause(), the constant `generation`\nstep(10);\n".to_string(),
+            },
+            rule_ast: None,
+            error: None,
+            commands,
+            queue: VecDeque::new(),
+        };
+        res.compile_rule();
+        res
+    }
+
+    fn index(x: INT, y: INT) -> Option<(usize, usize)> {
+        Some((usize::try_from(x).ok()?, usize::try_from(y).ok()?))
+    }
+
+    /// integers which are no cell type become `CellType::NoCell`
+    fn cell_type(state: INT) -> CellType {
+        usize::try_from(state)
+            .ok()
+            .and_then(|ix| ix.try_into().ok())
+            .unwrap_or(CellType::NoCell)
+    }
+
+    /// an array of rows of cell types
+    fn pattern(rows: Array) -> VecMatrix<CellType> {
+        let rows: Vec<Vec<CellType>> = rows
+            .into_iter()
+            .map(|row| {
+                row.try_cast::<Array>()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|state| Self::cell_type(state.as_int().unwrap_or(0)))
+                    .collect()
+            })
+            .collect();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut res = VecMatrix::new(width, rows.len(), CellType::NoCell);
+        for (ixy, row) in rows.iter().enumerate() {
+            for (ixx, cell) in row.iter().enumerate() {
+                res.set_at_index((ixx, ixy), *cell);
+            }
+        }
+        res
+    }
+
+    /// has to be called after `sources.rule` changed
+    pub fn compile_rule(&mut self) {
+        self.rule_ast = None;
+        match self.engine.compile(&self.sources.rule) {
+            Ok(ast) => {
+                if ast
+                    .iter_functions()
+                    .any(|f| f.name == "next" && f.params.len() == 5)
+                {
+                    self.rule_ast = Some(ast);
+                    self.error = None;
+                } else {
+                    self.error =
+                        Some("the rule script needs fn next(state, acc, x, y, gen)".into());
+                }
+            }
+            Err(err) => self.error = Some(format!("rule script: {err}")),
+        }
+    }
+
+    pub fn has_rule(&self) -> bool {
+        self.rule_ast.is_some()
+    }
+
+    /// calls `next` of the rule script, `Ok(state)` if there is no compiled rule script
+    /// the scope can be reused for every cell of a step, it is rewound after the call
+    pub fn next(
+        &self,
+        scope: &mut Scope,
+        state: CellType,
+        acc: FieldType,
+        (x, y): (usize, usize),
+        generation: usize,
+    ) -> Result<CellType, String> {
+        let ast = match &self.rule_ast {
+            Some(ast) => ast,
+            None => return Ok(state),
+        };
+        let len = scope.len();
+        let next = self.engine.call_fn::<INT>(
+            scope,
+            ast,
+            "next",
+            (
+                state.as_index() as INT,
+                acc as FLOAT,
+                x as INT,
+                y as INT,
+                generation as INT,
+            ),
+        );
+        scope.rewind(len);
+        let next = next.map_err(|err| format!("next: {err}"))?;
+        usize::try_from(next)
+            .ok()
+            .and_then(|ix| ix.try_into().ok())
+            .ok_or_else(|| format!("next returned {next}, which is not a cell type"))
+    }
+
+    /// runs the automation script and returns the commands it issued
+    pub fn run_automation(&self, generation: usize) -> Result<Vec<ScriptCommand>, String> {
+        self.commands.borrow_mut().clear();
+        let ast = self
+            .engine
+            .compile(&self.sources.automation)
+            .map_err(|err| format!("automation script: {err}"))?;
+        let mut scope = Scope::new();
+        scope.push_constant("generation", generation as INT);
+        self.engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|err| format!("automation script: {err}"))?;
+        Ok(self.commands.borrow_mut().drain(..).collect())
+    }
+}
+
+#[test]
+fn runs_the_scripts() {
+    let mut script = Script::new();
+    assert!(script.has_rule());
+    let mut scope = Scope::new();
+    assert_eq!(
+        script.next(&mut scope, CellType::NoCell, 3.0, (0, 0), 0),
+        Ok(CellType::A)
+    );
+    assert_eq!(
+        script.next(&mut scope, CellType::A, 5.0, (0, 0), 0),
+        Ok(CellType::NoCell)
+    );
+    assert!(scope.is_empty());
+    script.sources.automation =
+        "set_cell(1, 2, 3);\nstep(generation * 1000000);\npause();\n".into();
+    let commands = script.run_automation(7).unwrap();
+    assert!(matches!(
+        commands[..],
+        [
+            ScriptCommand::SetCell((1, 2), CellType::C),
+            ScriptCommand::Step(MAX_SCRIPT_STEPS),
+            ScriptCommand::Pause
+        ]
+    ));
+    script.sources.automation = "step(".into();
+    assert!(script.run_automation(0).is_err());
+}
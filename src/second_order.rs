@@ -0,0 +1,54 @@
+use crate::CellType;
+use matrices::{traits::Matrix, vec_matrix::VecMatrix};
+
+/// Second order automaton in the style of Fredkin:
+/// `next = (rules(current) - previous) mod states`
+/// with 2 states this is `rules(current) XOR previous`.
+/// Swapping the previous and the current generation runs the automaton backwards.
+pub struct SecondOrder {
+    /// cells of the previous generation
+    pub prev: VecMatrix<CellType>,
+    /// number of states the difference is taken modulo
+    pub states: usize,
+}
+
+impl SecondOrder {
+    pub fn new(width: usize, height: usize) -> Self {
+        SecondOrder {
+            prev: VecMatrix::new(width, height, CellType::NoCell),
+            states: 2,
+        }
+    }
+
+    /// clears the previous generation, has to be called when the board size changed
+    pub fn reset(&mut self, width: usize, height: usize) {
+        self.prev = VecMatrix::new(width, height, CellType::NoCell);
+    }
+
+    /// combines the result of the rules with the state of the previous generation
+    pub fn combine(&self, ruled: CellType, prev: CellType) -> CellType {
+        let states = self.states.clamp(2, 9);
+        let index = (ruled.as_index() % states + states - prev.as_index() % states) % states;
+        CellType::try_from(index).unwrap_or(CellType::NoCell)
+    }
+
+    /// swaps the previous and the current generation, the next steps undo the previous ones
+    pub fn reverse(&mut self, cells: &mut VecMatrix<CellType>) {
+        std::mem::swap(&mut self.prev, cells);
+    }
+}
+
+#[test]
+fn second_order_reverses() {
+    let mut s = SecondOrder::new(1, 1);
+    s.states = 3;
+    for ruled in 0..3 {
+        for prev in 0..3 {
+            let ruled = CellType::try_from(ruled).unwrap();
+            let prev = CellType::try_from(prev).unwrap();
+            // the rules see the same current generation in both directions
+            let next = s.combine(ruled, prev);
+            assert_eq!(s.combine(ruled, next), prev);
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::cell_type::CellType;
+use matrices::{traits::Matrix, vec_matrix::VecMatrix};
+
+/// Compares the incremental step, which only evaluates cells near changes,
+/// with a reference step which evaluates the whole board
+#[derive(Default)]
+pub struct SelfCheck {
+    /// cells which differ from the reference in the last checked generation, sorted
+    pub mismatches: Vec<(usize, usize)>,
+    /// generation of the last check which found a difference
+    pub last_mismatch: Option<usize>,
+    pub generations_checked: usize,
+    /// why the last step was not checked, only deterministic steps can be compared
+    pub skipped: Option<&'static str>,
+}
+
+impl SelfCheck {
+    /// returns true if `cells` are the same as `reference`
+    pub fn compare(
+        &mut self,
+        cells: &VecMatrix<CellType>,
+        reference: &VecMatrix<CellType>,
+        generation: usize,
+    ) -> bool {
+        self.skipped = None;
+        self.generations_checked += 1;
+        self.mismatches.clear();
+        for ixy in 0..cells.height() {
+            for ixx in 0..cells.width() {
+                if cells.index((ixx, ixy)) != reference.index((ixx, ixy)) {
+                    self.mismatches.push((ixx, ixy));
+                }
+            }
+        }
+        self.mismatches.sort_unstable();
+        if self.mismatches.is_empty() {
+            true
+        } else {
+            self.last_mismatch = Some(generation);
+            false
+        }
+    }
+
+    pub fn is_mismatch(&self, ix: (usize, usize)) -> bool {
+        self.mismatches.binary_search(&ix).is_ok()
+    }
+}
+
+#[test]
+fn finds_differing_cells() {
+    let mut self_check = SelfCheck::default();
+    let reference = VecMatrix::new(3, 2, CellType::NoCell);
+    let mut cells = reference.clone();
+    assert!(self_check.compare(&cells, &reference, 1));
+    cells.set_at_index((2, 1), CellType::A);
+    cells.set_at_index((0, 1), CellType::B);
+    assert!(!self_check.compare(&cells, &reference, 2));
+    assert_eq!(self_check.mismatches, vec![(0, 1), (2, 1)]);
+    assert!(self_check.is_mismatch((2, 1)) && !self_check.is_mismatch((1, 1)));
+    assert_eq!(self_check.last_mismatch, Some(2));
+    assert_eq!(self_check.generations_checked, 2);
+}
@@ -0,0 +1,94 @@
+use crate::{cell_type::CellType, rules::RuleSet, FieldType};
+use matrices::vec_matrix::VecMatrix;
+use serde::{Deserialize, Serialize};
+
+/// What happens when the generation counter reaches a keyframe
+#[derive(Serialize, Deserialize, Clone)]
+pub enum TimelineEvent {
+    /// replaces the rules of region 0
+    SetRules(RuleSet<FieldType>),
+    /// copies the cells onto the board with their top left corner at `position`
+    /// empty cells of the pattern leave the board unchanged
+    Stamp {
+        position: (usize, usize),
+        cells: VecMatrix<CellType>,
+    },
+    /// stops the simulation
+    Pause,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Keyframe {
+    pub generation: usize,
+    pub event: TimelineEvent,
+}
+
+/// Ramps the value of `cell_type` in the `CellTypeMap` linearly
+/// from `from` in generation `start` to `to` in generation `end`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ValueTrack {
+    pub cell_type: CellType,
+    pub start: usize,
+    pub end: usize,
+    pub from: FieldType,
+    pub to: FieldType,
+}
+
+impl ValueTrack {
+    /// None outside of `start..=end`
+    pub fn value_at(&self, generation: usize) -> Option<FieldType> {
+        if !(self.start..=self.end).contains(&generation) {
+            return None;
+        }
+        if self.start == self.end {
+            return Some(self.to);
+        }
+        let t = (generation - self.start) as FieldType / (self.end - self.start) as FieldType;
+        Some(self.from + t * (self.to - self.from))
+    }
+}
+
+/// Keyframed events and parameter tracks which are run after every step
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Timeline {
+    pub keyframes: Vec<Keyframe>,
+    pub tracks: Vec<ValueTrack>,
+}
+
+impl Timeline {
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty() && self.tracks.is_empty()
+    }
+
+    pub fn events_at(&self, generation: usize) -> impl Iterator<Item = &TimelineEvent> {
+        self.keyframes
+            .iter()
+            .filter(move |keyframe| keyframe.generation == generation)
+            .map(|keyframe| &keyframe.event)
+    }
+
+    /// values of all tracks which are active in `generation`
+    pub fn values_at(&self, generation: usize) -> impl Iterator<Item = (CellType, FieldType)> + '_ {
+        self.tracks.iter().filter_map(move |track| {
+            track
+                .value_at(generation)
+                .map(|value| (track.cell_type, value))
+        })
+    }
+}
+
+#[test]
+fn value_track_ramps_linearly() {
+    let track = ValueTrack {
+        cell_type: CellType::B,
+        start: 1000,
+        end: 2000,
+        from: 1.0,
+        to: 2.0,
+    };
+    assert_eq!(track.value_at(999), None);
+    assert_eq!(track.value_at(1000), Some(1.0));
+    assert_eq!(track.value_at(1500), Some(1.5));
+    assert_eq!(track.value_at(2000), Some(2.0));
+    assert_eq!(track.value_at(2001), None);
+}
@@ -0,0 +1,215 @@
+use crate::{render_mini::Vec2, FieldType};
+use matrices::traits::Matrix;
+use num_traits::{One, Zero};
+use serde::{Deserialize, Serialize};
+
+/// Shape of the cells and their neighbourhood
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Topology {
+    Square,
+    /// axial coordinates: the neighbours of (q, r) are
+    /// (q ± 1, r), (q, r ± 1), (q + 1, r - 1) and (q - 1, r + 1)
+    /// the board is drawn as a parallelogram
+    Hexagonal,
+    /// cells with an even x + y point up, cells with an odd x + y point down
+    /// kernels are defined for upward pointing cells and are mirrored vertically
+    /// for downward pointing cells
+    Triangular,
+}
+
+impl Topology {
+    /// true if the kernel has to be mirrored vertically for the cell at (ixx, ixy)
+    pub fn is_flipped(&self, (ixx, ixy): (usize, usize)) -> bool {
+        *self == Topology::Triangular && (ixx + ixy) % 2 == 1
+    }
+
+    /// number of corners of a cell
+    pub fn corners(&self) -> usize {
+        match self {
+            Topology::Square => 4,
+            Topology::Hexagonal => 6,
+            Topology::Triangular => 3,
+        }
+    }
+
+    /// corners of the cell at (ixx, ixy) in -1..=1 coordinates, y is pointing down
+    /// the cell is scaled around its center by `size_factor`
+    pub fn cell_polygon(
+        &self,
+        (ixx, ixy): (usize, usize),
+        (width, height): (usize, usize),
+        size_factor: f32,
+    ) -> Vec<Vec2> {
+        let (x, y) = (ixx as f32, ixy as f32);
+        let (corners, center) = match self {
+            Topology::Square => {
+                let cw = 2.0 / width as f32;
+                let ch = 2.0 / height as f32;
+                let (left, top) = (x * cw - 1.0, y * ch - 1.0);
+                (
+                    vec![
+                        (left, top),
+                        (left, top + ch),
+                        (left + cw, top + ch),
+                        (left + cw, top),
+                    ],
+                    (left + 0.5 * cw, top + 0.5 * ch),
+                )
+            }
+            Topology::Hexagonal => {
+                let (cw, ch) = Self::hex_cell_size(width, height);
+                let (cx, cy) = ((x + 0.5 * y + 0.5) * cw - 1.0, (y + 2.0 / 3.0) * ch - 1.0);
+                let (hw, third) = (0.5 * cw, ch / 3.0);
+                (
+                    vec![
+                        (cx, cy - 2.0 * third),
+                        (cx - hw, cy - third),
+                        (cx - hw, cy + third),
+                        (cx, cy + 2.0 * third),
+                        (cx + hw, cy + third),
+                        (cx + hw, cy - third),
+                    ],
+                    (cx, cy),
+                )
+            }
+            Topology::Triangular => {
+                let base = 4.0 / (width as f32 + 1.0);
+                let ch = 2.0 / height as f32;
+                let (left, top) = (x * 0.5 * base - 1.0, y * ch - 1.0);
+                let mid = left + 0.5 * base;
+                if self.is_flipped((ixx, ixy)) {
+                    (
+                        vec![(left, top), (mid, top + ch), (left + base, top)],
+                        (mid, top + ch / 3.0),
+                    )
+                } else {
+                    (
+                        vec![(left, top + ch), (left + base, top + ch), (mid, top)],
+                        (mid, top + 2.0 * ch / 3.0),
+                    )
+                }
+            }
+        };
+        corners
+            .into_iter()
+            .map(|(px, py)| Vec2 {
+                x: center.0 + (px - center.0) * size_factor,
+                y: center.1 + (py - center.1) * size_factor,
+            })
+            .collect()
+    }
+
+    /// width of a cell and the distance between two rows of a hexagonal board
+    fn hex_cell_size(width: usize, height: usize) -> (f32, f32) {
+        (
+            2.0 / (width as f32 + 0.5 * (height as f32 - 1.0)),
+            2.0 / (height as f32 + 1.0 / 3.0),
+        )
+    }
+
+    /// index of the cell at (px, py) in -1..=1 coordinates, y is pointing down
+    /// positions outside of the board are clamped to the nearest border cell
+    pub fn point_to_index(
+        &self,
+        (px, py): (f32, f32),
+        (width, height): (usize, usize),
+    ) -> (usize, usize) {
+        let (ixx, ixy) = match self {
+            Topology::Square => (
+                ((px + 1.0) * 0.5 * width as f32).floor(),
+                ((py + 1.0) * 0.5 * height as f32).floor(),
+            ),
+            Topology::Hexagonal => {
+                let (cw, ch) = Self::hex_cell_size(width, height);
+                let r = (py + 1.0) / ch - 2.0 / 3.0;
+                let q = (px + 1.0) / cw - 0.5 - 0.5 * r;
+                // cube coordinate rounding
+                let s = -q - r;
+                let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+                let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+                if dq > dr && dq > ds {
+                    rq = -rr - rs;
+                } else if dr > ds {
+                    rr = -rq - rs;
+                }
+                (rq, rr)
+            }
+            Topology::Triangular => {
+                let base = 4.0 / (width as f32 + 1.0);
+                let row_pos = (py + 1.0) * 0.5 * height as f32;
+                let ixy = row_pos.floor();
+                // 0 at the top of the row, 1 at the bottom
+                let depth = row_pos - ixy;
+                let u = (px + 1.0) / (0.5 * base);
+                let mut ixx = u.floor();
+                for candidate in [u.floor(), u.floor() - 1.0] {
+                    // distance to the vertical center line of the candidate
+                    let dist = (u - candidate - 1.0).abs();
+                    let bup = (candidate + ixy).rem_euclid(2.0) == 0.0;
+                    if (bup && dist <= depth) || (!bup && dist <= 1.0 - depth) {
+                        ixx = candidate;
+                        break;
+                    }
+                }
+                (ixx, ixy)
+            }
+        };
+        (
+            (ixx.max(0.0) as usize).min(width - 1),
+            (ixy.max(0.0) as usize).min(height - 1),
+        )
+    }
+
+    /// fills `kernel` with ones for all neighbours within `radius`
+    /// radius 1 of a triangular grid are the 3 edge neighbours,
+    /// radius 2 are the 12 cells which share at least a corner
+    pub fn neighbourhood_kernel<C: Matrix<FieldType>>(&self, kernel: &mut C, radius: usize) {
+        let wh = (kernel.width() / 2) as isize;
+        let radius = radius as isize;
+        for kixx in 0..kernel.width() {
+            for kixy in 0..kernel.height() {
+                let (dx, dy) = (kixx as isize - wh, kixy as isize - wh);
+                let bneighbour = match self {
+                    Topology::Square => dx.abs().max(dy.abs()) <= radius,
+                    Topology::Hexagonal => {
+                        dx.abs() <= radius && dy.abs() <= radius && (dx + dy).abs() <= radius
+                    }
+                    Topology::Triangular => match radius {
+                        0 => false,
+                        1 => (dy == 0 && dx.abs() == 1) || (dx == 0 && dy == 1),
+                        _ => match dy {
+                            -1 => dx.abs() <= 1,
+                            0 => dx.abs() <= 2,
+                            1 => dx.abs() <= 2,
+                            _ => false,
+                        },
+                    },
+                };
+                let val = if bneighbour && (dx, dy) != (0, 0) {
+                    One::one()
+                } else {
+                    Zero::zero()
+                };
+                kernel.set_at_index((kixx, kixy), val);
+            }
+        }
+    }
+}
+
+#[test]
+fn cell_centers_map_to_their_index() {
+    let size = (9, 7);
+    for topology in [Topology::Square, Topology::Hexagonal, Topology::Triangular] {
+        for ixx in 0..size.0 {
+            for ixy in 0..size.1 {
+                let polygon = topology.cell_polygon((ixx, ixy), size, 1.0);
+                let n = polygon.len() as f32;
+                let center = (
+                    polygon.iter().map(|p| p.x).sum::<f32>() / n,
+                    polygon.iter().map(|p| p.y).sum::<f32>() / n,
+                );
+                assert_eq!(topology.point_to_index(center, size), (ixx, ixy));
+            }
+        }
+    }
+}
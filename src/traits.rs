@@ -1,4 +1,4 @@
-use crate::{index_set::IndexSet, CellType};
+use crate::{convolution::KernelParams, index_set::IndexSet, CellType};
 use matrices::traits::Matrix;
 use matrices::vec_matrix::VecMatrix;
 
@@ -6,8 +6,7 @@ pub trait ConvolutionT<Conv: Matrix<T>, T: Copy, Acc: Matrix<T>> {
     /// places accumulated values in self
     fn convolution(
         &self,
-        kernels: &[Conv],
-        single_kernel: bool,
+        params: &KernelParams<T, Conv>,
         cell_type_matrix: &VecMatrix<CellType>,
         acc_matrix: &mut Acc,
         indices: &IndexSet,
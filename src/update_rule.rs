@@ -0,0 +1,92 @@
+use crate::{cell_type::CellType, topology::Topology, FieldType};
+use matrices::{traits::Matrix, vec_matrix::VecMatrix};
+
+/// What an `UpdateRule` can see of a cell besides its state and accumulated value
+pub struct NeighbourhoodCtx<'a> {
+    pub position: (usize, usize),
+    pub generation: usize,
+    /// None if ages are not tracked
+    pub age: Option<u32>,
+    pub cells: &'a VecMatrix<CellType>,
+    pub topology: Topology,
+}
+
+impl<'a> NeighbourhoodCtx<'a> {
+    /// the cell at the offset `(dx, dy)` from this cell, None outside of the board
+    pub fn neighbour(&self, dx: isize, dy: isize) -> Option<CellType> {
+        let x = usize::try_from(self.position.0 as isize + dx).ok()?;
+        let y = usize::try_from(self.position.1 as isize + dy).ok()?;
+        (x < self.cells.width() && y < self.cells.height()).then(|| self.cells.index((x, y)))
+    }
+}
+
+/// A transition function written in Rust
+/// the discrete mode calls it instead of `RuleSet::apply` when it is selected in the UI
+pub trait UpdateRule {
+    fn apply(&self, state: CellType, acc: FieldType, ctx: &NeighbourhoodCtx) -> CellType;
+
+    /// true if the result can change while the neighbourhood stays the same,
+    /// for example because it depends on the position, the generation or chance
+    /// rules like this are evaluated on the whole board in every step
+    fn bevery_cell(&self) -> bool {
+        false
+    }
+}
+
+/// Named `UpdateRule`s which can be selected in the UI
+#[derive(Default)]
+pub struct RuleRegistry {
+    rules: Vec<(String, Box<dyn UpdateRule>)>,
+}
+
+impl RuleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the rules which come with rugol
+    pub fn with_builtins() -> Self {
+        let mut res = Self::new();
+        res.register("parity", Parity);
+        res
+    }
+
+    /// a rule with the same name is replaced
+    pub fn register(&mut self, name: impl Into<String>, rule: impl UpdateRule + 'static) {
+        let name = name.into();
+        let rule: Box<dyn UpdateRule> = Box::new(rule);
+        match self.rules.iter_mut().find(|(n, _)| *n == name) {
+            Some(entry) => entry.1 = rule,
+            None => self.rules.push((name, rule)),
+        }
+    }
+
+    pub fn get(&self, ix: usize) -> Option<&dyn UpdateRule> {
+        self.rules.get(ix).map(|(_, rule)| rule.as_ref())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// Fredkin's replicator: a cell is alive if the accumulated value is odd
+pub struct Parity;
+
+impl UpdateRule for Parity {
+    fn apply(&self, _state: CellType, acc: FieldType, _ctx: &NeighbourhoodCtx) -> CellType {
+        if (acc.round() as i64).rem_euclid(2) == 1 {
+            CellType::A
+        } else {
+            CellType::NoCell
+        }
+    }
+}